@@ -1,21 +1,59 @@
+use crate::mask::*;
 use crate::*;
 use bevy::math::vec2;
 use bevy::prelude::*;
 use bevy::render::Extract;
 use bevy::sprite::ExtractedSprite;
 use bevy::sprite::ExtractedSprites;
+use std::f32::consts::TAU;
 
 /// The z depth the stat bar sprites are drawn with.
-const DEFAULT_Z_DEPTH: f32 = 990.0;
+pub(crate) const DEFAULT_Z_DEPTH: f32 = 990.0;
+
+/// The largest angle covered by a single quad of a radial bar.
+const MAX_SEGMENT_ANGLE: f32 = TAU / 72.;
+
+/// How far the alpha of a pulsing preview dips, as a fraction of its color's alpha.
+const PREVIEW_PULSE_DEPTH: f32 = 0.6;
+
+/// The length of each single colored strip of a gradient painted along a bar.
+const GRADIENT_STRIP_LENGTH: f32 = 4.;
+
+/// Upper limit on the number of strips used to paint a gradient along a bar.
+const MAX_GRADIENT_STRIPS: f32 = 64.;
+
+/// Upper limit on the number of overflow layers, higher values are drawn as a full top layer.
+const MAX_OVERFLOW_LAYERS: f32 = 1000.;
+
+/// Upper limit on the number of overflow markers drawn past the end of a bar.
+const MAX_OVERFLOW_MARKERS: u32 = 10;
 
 #[allow(clippy::type_complexity)]
 pub(crate) fn extract_stat_bars<V: TypePath>(
     extraction: Extract<(
         Option<Res<StatbarDepth>>,
+        Res<Assets<Image>>,
+        Option<Res<Time>>,
+        Option<Res<StatbarTypeVisibility<V>>>,
         Query<(
             Entity,
             &Statbar<V>,
             Option<&StatbarBorder<V>>,
+            Option<&StatbarTexture<V>>,
+            Option<&StatbarColorGradient<V>>,
+            Option<&StatbarSegments<V>>,
+            Option<&StatbarIcons<V>>,
+            Option<&StatbarOverflow<V>>,
+            Option<&StatbarStack<V>>,
+            (
+                Option<&StatbarPreview<V>>,
+                Option<&StatbarTrail<V>>,
+                Option<&StatbarFlash<V>>,
+                Option<&StatbarShake<V>>,
+                Option<&StatbarAlert<V>>,
+                Option<&StatbarAutoHide<V>>,
+                Option<&StatbarShowOnFocus<V>>,
+            ),
             &GlobalTransform,
             &InheritedVisibility,
         )>,
@@ -24,11 +62,62 @@ pub(crate) fn extract_stat_bars<V: TypePath>(
     mut commands: Commands,
 ) {
     let mut new_translation;
-    let (depth, query) = &*extraction;
-    for (id, bar, border, global_transform, computed_visibility) in query.iter() {
-        if bar.hide || !computed_visibility.get() {
+    let (depth, images, time, type_visibility, query) = &*extraction;
+    // without a time resource the pulsing effects hold still
+    let elapsed_seconds = time
+        .as_ref()
+        .map_or(0., |time| time.elapsed_seconds_wrapped());
+    if type_visibility
+        .as_ref()
+        .is_some_and(|type_visibility| !type_visibility.is_visible())
+    {
+        return;
+    }
+    for (
+        id,
+        bar,
+        border,
+        texture,
+        gradient,
+        segments,
+        icons,
+        overflow,
+        stack,
+        (preview, trail, flash, shake, alert, auto_hide, focus),
+        global_transform,
+        computed_visibility,
+    ) in query.iter()
+    {
+        let opacity = auto_hide.map_or(1., |auto_hide| auto_hide.opacity);
+        let unfocused = focus.is_some_and(|focus| !focus.is_shown());
+        if bar.hide || !computed_visibility.get() || opacity <= 0. || unfocused {
             continue;
         }
+        // strength of the alert effect, 0.0 => no effect
+        let alert_wave = alert
+            .filter(|alert| bar.value < alert.threshold)
+            .map(|alert| alert.wave(elapsed_seconds))
+            .unwrap_or(0.);
+        let (alpha, brightness, thickness_scale) = match alert.map(|alert| alert.effect) {
+            Some(StatbarAlertEffect::Blink { min_alpha }) => {
+                (1. + (min_alpha - 1.) * alert_wave, 1., 1.)
+            }
+            Some(StatbarAlertEffect::Pulse {
+                thickness,
+                brightness,
+            }) => (
+                1.,
+                1. + brightness * alert_wave,
+                1. + thickness * alert_wave,
+            ),
+            None => (1., 1., 1.),
+        };
+        let mut sprites = BarSprites {
+            extracted_sprites: &mut extracted_sprites,
+            commands: &mut commands,
+            entity: id,
+            alpha: alpha * opacity,
+        };
         let (major_axis, minor_axis) = if bar.vertical {
             (Vec2::Y, Vec2::X)
         } else {
@@ -37,7 +126,7 @@ pub(crate) fn extract_stat_bars<V: TypePath>(
 
         let value = bar.value;
         let length = bar.length;
-        let thickness = bar.thickness;
+        let thickness = bar.thickness * thickness_scale;
         new_translation = global_transform.translation();
         let z = depth
             .as_ref()
@@ -46,68 +135,794 @@ pub(crate) fn extract_stat_bars<V: TypePath>(
         new_translation.z = z;
         new_translation.x += bar.displacement.x;
         new_translation.y += bar.displacement.y;
+        if let Some(shake) = shake {
+            new_translation += shake.offset.extend(0.);
+        }
+        // applies the flash and alert effects to the colors of the bar
+        let tint = |color: LinearRgba| {
+            let color = match flash {
+                Some(flash) => flash.apply(color),
+                None => color,
+            };
+            LinearRgba {
+                alpha: color.alpha,
+                ..color * brightness
+            }
+        };
+        let empty_color = tint(bar.empty_color.to_linear());
+        // color of the full part of the bar at each position along its length
+        let gradient = gradient.filter(|gradient| {
+            gradient.mode == StatbarGradientMode::Length && !gradient.stops.is_empty()
+        });
+        // only straight, continuous bars are drawn as bipolar bars
+        let bipolar = bar
+            .bipolar
+            .filter(|_| bar.radial.is_none() && bar.pips.is_none() && icons.is_none());
+        let fill_color = |position: f32| {
+            tint(match (gradient, bipolar) {
+                (Some(gradient), _) => gradient.sample(position).to_linear(),
+                (None, Some(bipolar)) if position < 0.5 => bipolar.negative_color.to_linear(),
+                _ => bar.display_color.to_linear(),
+            })
+        };
+        if let Some(radial) = bar.radial {
+            let center = new_translation.truncate();
+            let full_angle = radial.angle(1.);
+            if let Some(border) = border {
+                let inner_radius = (radial.inner_radius - border.bottom).max(0.);
+                let outer_radius = radial.outer_radius + border.top;
+                let (start_angle, angle) = if full_angle.abs() < TAU {
+                    // extend the ends of the arc by the border thickness at its middle
+                    let mid_radius = 0.5 * (inner_radius + outer_radius);
+                    let direction = full_angle.signum();
+                    let start_angle = radial.start_angle - direction * border.left / mid_radius;
+                    let angle = full_angle + direction * (border.left + border.right) / mid_radius;
+                    (start_angle, angle.clamp(-TAU, TAU))
+                } else {
+                    (radial.start_angle, full_angle)
+                };
+                sprites.arc(
+                    center,
+                    inner_radius,
+                    outer_radius,
+                    start_angle,
+                    angle,
+                    z,
+                    |_| border.color.to_linear(),
+                );
+            }
+            if value < 1.0 {
+                sprites.arc(
+                    center,
+                    radial.inner_radius,
+                    radial.outer_radius,
+                    radial.start_angle,
+                    full_angle,
+                    z + 1.0,
+                    |_| empty_color,
+                );
+            }
+            if let Some(trail) = trail.filter(|trail| value < trail.value) {
+                let from = value.clamp(0., 1.);
+                let to = trail.value.clamp(0., 1.);
+                sprites.arc(
+                    center,
+                    radial.inner_radius,
+                    radial.outer_radius,
+                    radial.start_angle + radial.angle(from),
+                    radial.angle(to) - radial.angle(from),
+                    z + 1.5,
+                    |_| trail.color.to_linear(),
+                );
+            }
+            if 0.0 < value {
+                sprites.arc(
+                    center,
+                    radial.inner_radius,
+                    radial.outer_radius,
+                    radial.start_angle,
+                    radial.angle(value),
+                    z + 2.0,
+                    |angle| fill_color(angle / full_angle),
+                );
+            }
+            if let Some((from, to, color)) =
+                preview.and_then(|preview| preview_section(preview, value, 1., elapsed_seconds))
+            {
+                let start_angle = radial.start_angle + radial.angle(from);
+                sprites.arc(
+                    center,
+                    radial.inner_radius,
+                    radial.outer_radius,
+                    start_angle,
+                    radial.angle(to) - radial.angle(from),
+                    z + 2.1,
+                    |_| color,
+                );
+            }
+            if let Some(layers) = overflow.and_then(|overflow| OverflowLayers::new(overflow, value))
+            {
+                for (fill, color, z) in layers.draw_order(z) {
+                    sprites.arc(
+                        center,
+                        radial.inner_radius,
+                        radial.outer_radius,
+                        radial.start_angle,
+                        radial.angle(fill),
+                        z,
+                        |_| color,
+                    );
+                }
+            }
+            if let Some(segments) = segments {
+                let size = vec2(radial.outer_radius - radial.inner_radius, segments.width);
+                let mid_radius = 0.5 * (radial.inner_radius + radial.outer_radius);
+                let arc_length = mid_radius * radial.angle(1.).abs();
+                for separator in segments.separators(bar.max, arc_length) {
+                    let angle = radial.start_angle + radial.angle(separator);
+                    let translation = center + mid_radius * Vec2::from_angle(angle);
+                    sprites.emit(
+                        Transform::from_translation(translation.extend(z + 3.0))
+                            .with_rotation(Quat::from_rotation_z(angle)),
+                        size,
+                        segments.color.to_linear(),
+                        AssetId::default(),
+                        None,
+                    );
+                }
+            }
+            continue;
+        }
         let size = length * major_axis + thickness * minor_axis;
+        let bar_rect = Rect::from_center_size(new_translation.truncate(), size);
+        let fill_image = texture.and_then(|texture| texture.fill.as_ref());
+        let empty_image = texture.and_then(|texture| texture.empty.as_ref());
+        let border_image = texture.and_then(|texture| texture.border.as_ref());
         if let Some(border) = border {
-            let border_size = vec2(
-                size.x + border.left + border.right,
-                size.y + border.bottom + border.top,
-            );
-            extracted_sprites.sprites.insert(
-                commands.spawn_empty().id(),
-                ExtractedSprite {
-                    transform: GlobalTransform::from_translation(new_translation),
-                    color: border.color.to_linear(),
-                    rect: None,
-                    custom_size: Some(border_size),
-                    image_handle_id: AssetId::default(),
-                    flip_x: false,
-                    flip_y: false,
-                    anchor: Default::default(),
-                    original_entity: Some(id),
-                },
-            );
+            let border_rect = Rect {
+                min: bar_rect.min - vec2(border.left, border.bottom),
+                max: bar_rect.max + vec2(border.right, border.top),
+            };
+            let color = border.color.to_linear();
+            let nine_slice_image = border_image
+                .filter(|_| border.nine_slice)
+                .and_then(|image| Some((image.id(), images.get(image)?.size_f32())));
+            let radius = border.shape.radius(border_rect.size());
+            if let Some((image_id, image_size)) = nine_slice_image {
+                let slices = NineSlice {
+                    area: border_rect,
+                    insets: [border.left, border.right, border.bottom, border.top],
+                    image_size,
+                    image_insets: border.image_insets.unwrap_or([
+                        border.left,
+                        border.right,
+                        border.bottom,
+                        border.top,
+                    ]),
+                };
+                sprites.nine_slice(&slices, border_rect, z, color, image_id);
+            } else if border_image.is_none() && 0. < radius {
+                sprites.nine_slice(
+                    &NineSlice::rounded(border_rect, radius),
+                    border_rect,
+                    z,
+                    color,
+                    ROUNDED_MASK_HANDLE.id(),
+                );
+            } else {
+                sprites.quad(
+                    border_rect.center().extend(z),
+                    border_rect.size(),
+                    color,
+                    image_id(border_image),
+                    None,
+                );
+            }
+        }
+
+        let radius = bar.shape.radius(size);
+
+        let continuous = icons.is_none() && bar.pips.is_none();
+        if let Some(icons) = icons {
+            let (count, value) = icons.fill(value, bar.max);
+            for (icon, (from, to)) in cells(count, icons.gap, length).enumerate() {
+                let icon_rect = section(bar_rect, bar.vertical, bar.reverse, from, to);
+                let mut fill = (value - icon as f32).clamp(0., 1.);
+                if let Some(steps) = icons.steps.filter(|&steps| 0 < steps) {
+                    fill = (fill * steps as f32).floor() / steps as f32;
+                }
+                if fill < 1. {
+                    if let Some(empty) = icons.empty.as_ref() {
+                        sprites.quad(
+                            icon_rect.center().extend(z + 1.0),
+                            icon_rect.size(),
+                            empty_color,
+                            empty.id(),
+                            None,
+                        );
+                    }
+                }
+                if 0. < fill {
+                    let clip = section(icon_rect, bar.vertical, bar.reverse, 0., fill);
+                    sprites.quad(
+                        clip.center().extend(z + 2.0),
+                        clip.size(),
+                        tint(bar.display_color.to_linear()),
+                        icons.full.id(),
+                        fill_rect(images, &icons.full, 0., fill, bar.vertical, bar.reverse),
+                    );
+                }
+            }
+        } else if let Some(pips) = bar.pips {
+            let count = pips.count.max(1);
+            let value = value.clamp(0., 1.) * count as f32;
+            for (pip, (from, to)) in cells(count, pips.gap, length).enumerate() {
+                let pip_rect = section(bar_rect, bar.vertical, bar.reverse, from, to);
+                let pip_radius = bar.shape.radius(pip_rect.size());
+                let fill = (value - pip as f32).clamp(0., 1.);
+                if fill < 1. {
+                    sprites.shape(pip_rect, pip_radius, pip_rect, z + 1.0, empty_color);
+                }
+                if 0. < fill {
+                    let clip = section(pip_rect, bar.vertical, bar.reverse, 0., fill);
+                    let color = fill_color(0.5 * (from + to));
+                    sprites.shape(pip_rect, pip_radius, clip, z + 2.0, color);
+                }
+            }
         }
 
+        let stack = stack.filter(|_| continuous && bipolar.is_none());
+        // shrinks the bar's full part and the stacked segments to fit the bar together
+        let stack_scale = stack
+            .map(|stack| (value.max(0.) + stack.total()).max(1.))
+            .unwrap_or(1.);
+
+        // the full part of the bar, as fractions of its length
+        let (fill_from, fill_to) = match bipolar {
+            Some(_) => {
+                let value = value.clamp(-1., 1.);
+                (0.5 + 0.5 * value.min(0.), 0.5 + 0.5 * value.max(0.))
+            }
+            None => (0., value.clamp(0., 1.) / stack_scale),
+        };
+
         // draw bar back
-        if value < 1.0 {
-            new_translation.z = z + 1.0;
-            extracted_sprites.sprites.insert(
-                commands.spawn_empty().id(),
-                ExtractedSprite {
-                    transform: GlobalTransform::from_translation(new_translation),
-                    color: bar.empty_color.to_linear(),
-                    rect: None,
-                    custom_size: Some(size),
-                    image_handle_id: AssetId::default(),
-                    flip_x: false,
-                    flip_y: false,
-                    anchor: Default::default(),
-                    original_entity: Some(id),
-                },
-            );
+        if (0. < fill_from || fill_to < 1.) && continuous {
+            if empty_image.is_none() && 0. < radius {
+                sprites.nine_slice(
+                    &NineSlice::rounded(bar_rect, radius),
+                    bar_rect,
+                    z + 1.0,
+                    empty_color,
+                    ROUNDED_MASK_HANDLE.id(),
+                );
+            } else {
+                sprites.quad(
+                    new_translation.truncate().extend(z + 1.0),
+                    size,
+                    empty_color,
+                    image_id(empty_image),
+                    None,
+                );
+            }
+        }
+
+        // draw the trail left behind by a drop in value
+        let trail = trail.filter(|_| continuous && bipolar.is_none());
+        if let Some(trail) = trail.filter(|trail| value < trail.value) {
+            let to = (trail.value.clamp(0., 1.) / stack_scale).max(fill_to);
+            let clip = section(bar_rect, bar.vertical, bar.reverse, fill_to, to);
+            sprites.shape(bar_rect, radius, clip, z + 1.5, trail.color.to_linear());
         }
 
         // draw bar
-        if 0.0 < value {
-            let value = value.clamp(0., 1.);
-            let bar_size = value * length * major_axis + thickness * minor_axis;
-            let direction = if bar.reverse { -1. } else { 1. };
-            new_translation += direction * 0.5 * length * (value - 1.) * major_axis.extend(0.);
-            new_translation.z = z + 2.0;
-            extracted_sprites.sprites.insert(
-                commands.spawn_empty().id(),
-                ExtractedSprite {
-                    transform: GlobalTransform::from_translation(new_translation),
-                    color: bar.color.to_linear(),
-                    rect: None,
-                    custom_size: Some(bar_size),
-                    image_handle_id: AssetId::default(),
-                    flip_x: false,
-                    flip_y: false,
-                    anchor: Default::default(),
-                    original_entity: Some(id),
-                },
+        if fill_from < fill_to && continuous {
+            if let Some(fill_image) = fill_image {
+                let clip = section(bar_rect, bar.vertical, bar.reverse, fill_from, fill_to);
+                sprites.quad(
+                    clip.center().extend(z + 2.0),
+                    clip.size(),
+                    fill_color(0.5 * (fill_from + fill_to)),
+                    fill_image.id(),
+                    fill_rect(
+                        images,
+                        fill_image,
+                        fill_from,
+                        fill_to,
+                        bar.vertical,
+                        bar.reverse,
+                    ),
+                );
+            } else {
+                // a gradient along the bar is painted in strips, each a single color,
+                // a bipolar bar in two strips, one for each side
+                let strips = if gradient.is_some() {
+                    (length / GRADIENT_STRIP_LENGTH)
+                        .ceil()
+                        .clamp(1., MAX_GRADIENT_STRIPS)
+                } else if bipolar.is_some() {
+                    2.
+                } else {
+                    1.
+                };
+                for strip in 0..strips as usize {
+                    let from = strip as f32 / strips;
+                    let to = (strip + 1) as f32 / strips;
+                    if to <= fill_from || fill_to <= from {
+                        continue;
+                    }
+                    let clip = section(
+                        bar_rect,
+                        bar.vertical,
+                        bar.reverse,
+                        from.max(fill_from),
+                        to.min(fill_to),
+                    );
+                    let color = fill_color(0.5 * (from + to));
+                    sprites.shape(bar_rect, radius, clip, z + 2.0, color);
+                }
+            }
+        }
+
+        // draw the stacked segments after the full part of the bar
+        if let Some(stack) = stack {
+            let mut from = fill_to;
+            for segment in &stack.segments {
+                let to = (from + segment.value.max(0.) / stack_scale).min(1.);
+                if from < to {
+                    let clip = section(bar_rect, bar.vertical, bar.reverse, from, to);
+                    sprites.shape(bar_rect, radius, clip, z + 2.0, segment.color.to_linear());
+                }
+                from = to;
+            }
+        }
+
+        // draw the previewed change at the edge of the full part of the bar
+        let preview = preview.filter(|_| continuous && bipolar.is_none());
+        if let Some((from, to, color)) = preview
+            .and_then(|preview| preview_section(preview, fill_to, stack_scale, elapsed_seconds))
+        {
+            let clip = section(bar_rect, bar.vertical, bar.reverse, from, to);
+            sprites.shape(bar_rect, radius, clip, z + 2.1, color);
+        }
+
+        // draw the part of the value above full over the bar
+        let overflow = overflow.filter(|_| continuous && bipolar.is_none());
+        if let Some(layers) = overflow.and_then(|overflow| OverflowLayers::new(overflow, value)) {
+            for (fill, color, z) in layers.draw_order(z) {
+                let clip = section(bar_rect, bar.vertical, bar.reverse, 0., fill);
+                sprites.shape(bar_rect, radius, clip, z, color);
+            }
+            if let Some(marker_color) = overflow.and_then(|overflow| overflow.marker_color) {
+                // one marker for each layer, past the full end of the bar
+                if 2 <= layers.count {
+                    let marker_size = 0.5 * thickness;
+                    let direction = if bar.reverse { -1. } else { 1. };
+                    let end = section(bar_rect, bar.vertical, bar.reverse, 1., 1.).center();
+                    for marker in 0..layers.count.min(MAX_OVERFLOW_MARKERS) {
+                        let distance = (marker as f32 + 1.) * 1.5 * marker_size;
+                        sprites.quad(
+                            (end + direction * distance * major_axis).extend(z + 2.5),
+                            Vec2::splat(marker_size),
+                            marker_color.to_linear(),
+                            AssetId::default(),
+                            None,
+                        );
+                    }
+                }
+            }
+        }
+
+        // draw segment separators
+        if let Some(segments) = segments {
+            let separator_size = segments.width * major_axis + thickness * minor_axis;
+            for separator in segments.separators(bar.max, length) {
+                let line = section(bar_rect, bar.vertical, bar.reverse, separator, separator);
+                sprites.quad(
+                    line.center().extend(z + 3.0),
+                    separator_size,
+                    segments.color.to_linear(),
+                    AssetId::default(),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// The start, end and color of the section of the bar covered by a preview,
+/// for a bar filled to `fill` with its values shrunk by `scale`
+fn preview_section<V>(
+    preview: &StatbarPreview<V>,
+    fill: f32,
+    scale: f32,
+    elapsed_seconds: f32,
+) -> Option<(f32, f32, LinearRgba)> {
+    let fill = fill.clamp(0., 1.);
+    let target = (fill + preview.amount / scale).clamp(0., 1.);
+    if target == fill {
+        return None;
+    }
+    let mut color = if fill < target {
+        preview.gain_color
+    } else {
+        preview.cost_color
+    };
+    if let Some(frequency) = preview.pulse {
+        let pulse = 0.5 + 0.5 * (TAU * frequency * elapsed_seconds).sin();
+        color.set_alpha(color.alpha() * (1. - PREVIEW_PULSE_DEPTH * pulse));
+    }
+    Some((fill.min(target), fill.max(target), color.to_linear()))
+}
+
+/// The overflow layers shown for a value above 1.0
+struct OverflowLayers {
+    /// color of the completely full layer underneath the top layer, if there is one
+    below: Option<LinearRgba>,
+    /// color of the top layer
+    top: LinearRgba,
+    /// how full the top layer is
+    fill: f32,
+    /// number of the top layer, 1 for values between 1.0 and 2.0
+    count: u32,
+}
+
+impl OverflowLayers {
+    fn new<V>(overflow: &StatbarOverflow<V>, value: f32) -> Option<Self> {
+        if !(value.is_finite() && value > 1.) || overflow.colors.is_empty() {
+            return None;
+        }
+        let overflow_value = if overflow.wrap {
+            (value - 1.).min(MAX_OVERFLOW_LAYERS)
+        } else {
+            (value - 1.).min(1.)
+        };
+        let count = overflow_value.ceil() as u32;
+        let color = |layer: u32| overflow.colors[(layer - 1) as usize % overflow.colors.len()];
+        Some(Self {
+            below: (2 <= count).then(|| color(count - 1).to_linear()),
+            top: color(count).to_linear(),
+            fill: overflow_value - (count - 1) as f32,
+            count,
+        })
+    }
+
+    /// The fill, color and depth of each layer to draw, bottom layer first
+    fn draw_order(&self, z: f32) -> impl Iterator<Item = (f32, LinearRgba, f32)> {
+        let below = self.below.map(|color| (1., color, z + 2.25));
+        below.into_iter().chain([(self.fill, self.top, z + 2.5)])
+    }
+}
+
+/// Emits the sprites for a single statbar
+struct BarSprites<'a, 'w, 's> {
+    extracted_sprites: &'a mut ExtractedSprites,
+    commands: &'a mut Commands<'w, 's>,
+    /// the entity with the Statbar component
+    entity: Entity,
+    /// multiplies the alpha of every sprite
+    alpha: f32,
+}
+
+impl BarSprites<'_, '_, '_> {
+    fn quad(
+        &mut self,
+        translation: Vec3,
+        size: Vec2,
+        color: LinearRgba,
+        image_handle_id: AssetId<Image>,
+        rect: Option<Rect>,
+    ) {
+        self.emit(
+            Transform::from_translation(translation),
+            size,
+            color,
+            image_handle_id,
+            rect,
+        );
+    }
+
+    /// Draws the parts of `area` inside `clip`, with its corners rounded to `radius`
+    fn shape(&mut self, area: Rect, radius: f32, clip: Rect, z: f32, color: LinearRgba) {
+        if 0. < radius {
+            let slices = NineSlice::rounded(area, radius);
+            self.nine_slice(&slices, clip, z, color, ROUNDED_MASK_HANDLE.id());
+        } else {
+            let visible = area.intersect(clip);
+            if 0. < visible.width() && 0. < visible.height() {
+                self.quad(
+                    visible.center().extend(z),
+                    visible.size(),
+                    color,
+                    AssetId::default(),
+                    None,
+                );
+            }
+        }
+    }
+
+    /// Draws an arc from `start_angle` turning through the signed `angle`,
+    /// approximated by a trapezoid for each segment.
+    /// `color` is called with the angle from `start_angle` to the middle of each segment.
+    ///
+    /// The trapezoids are quads cropped by the wedge mask, so neighbouring segments share
+    /// their edges without overlapping and translucent arcs have no seams.
+    #[allow(clippy::too_many_arguments)]
+    fn arc(
+        &mut self,
+        center: Vec2,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: f32,
+        angle: f32,
+        z: f32,
+        color: impl Fn(f32) -> LinearRgba,
+    ) {
+        if outer_radius <= 0. {
+            return;
+        }
+        let segments = (angle.abs() / MAX_SEGMENT_ANGLE).ceil().max(1.);
+        let step = angle / segments;
+        let half_step = 0.5 * step.abs();
+        // distances from the center to the inner and outer chords of each segment
+        let near = inner_radius * half_step.cos();
+        let far = outer_radius * half_step.cos();
+        let mid_radius = 0.5 * (near + far);
+        let size = vec2(far - near, 2. * outer_radius * half_step.sin());
+        let mask_size = WEDGE_MASK_SIZE as f32;
+        let rect = Rect::new(
+            mask_size * inner_radius / outer_radius,
+            0.,
+            mask_size,
+            mask_size,
+        );
+        for segment in 0..segments as usize {
+            let offset = (segment as f32 + 0.5) * step;
+            let segment_angle = start_angle + offset;
+            let translation = center + mid_radius * Vec2::from_angle(segment_angle);
+            self.emit(
+                Transform::from_translation(translation.extend(z))
+                    .with_rotation(Quat::from_rotation_z(segment_angle)),
+                size,
+                color(offset),
+                WEDGE_MASK_HANDLE.id(),
+                Some(rect),
             );
         }
     }
+
+    fn emit(
+        &mut self,
+        transform: Transform,
+        size: Vec2,
+        color: LinearRgba,
+        image_handle_id: AssetId<Image>,
+        rect: Option<Rect>,
+    ) {
+        self.extracted_sprites.sprites.insert(
+            self.commands.spawn_empty().id(),
+            ExtractedSprite {
+                transform: GlobalTransform::from(transform),
+                color: color.with_alpha(color.alpha * self.alpha),
+                rect,
+                custom_size: Some(size),
+                image_handle_id,
+                flip_x: false,
+                flip_y: false,
+                anchor: Default::default(),
+                original_entity: Some(self.entity),
+            },
+        );
+    }
+
+    /// Draws the parts of the nine-slice that lie inside `clip`
+    fn nine_slice(
+        &mut self,
+        slices: &NineSlice,
+        clip: Rect,
+        z: f32,
+        color: LinearRgba,
+        image_handle_id: AssetId<Image>,
+    ) {
+        let [left, right, bottom, top] = slices.insets;
+        let [image_left, image_right, image_bottom, image_top] = slices.image_insets;
+        let Rect { min, max } = slices.area;
+        let image_size = slices.image_size;
+        let xs = [min.x, min.x + left, max.x - right, max.x];
+        let ys = [min.y, min.y + bottom, max.y - top, max.y];
+        let us = [0., image_left, image_size.x - image_right, image_size.x];
+        // image space has y increasing downwards
+        let vs = [image_size.y, image_size.y - image_bottom, image_top, 0.];
+        for i in 0..3 {
+            for j in 0..3 {
+                let slice = Rect::new(xs[i], ys[j], xs[i + 1], ys[j + 1]);
+                let visible = slice.intersect(clip);
+                if visible.width() <= 0. || visible.height() <= 0. {
+                    continue;
+                }
+                // crop the image rect in proportion to the visible part of the slice
+                let to_u = |x: f32| us[i] + (us[i + 1] - us[i]) * (x - slice.min.x) / slice.width();
+                let to_v =
+                    |y: f32| vs[j] + (vs[j + 1] - vs[j]) * (y - slice.min.y) / slice.height();
+                let rect = Rect::new(
+                    to_u(visible.min.x),
+                    to_v(visible.min.y),
+                    to_u(visible.max.x),
+                    to_v(visible.max.y),
+                );
+                self.quad(
+                    visible.center().extend(z),
+                    visible.size(),
+                    color,
+                    image_handle_id,
+                    Some(rect),
+                );
+            }
+        }
+    }
+}
+
+/// An image stretched over `area` with its corners kept unscaled
+struct NineSlice {
+    area: Rect,
+    /// left, right, bottom and top insets of the slices in world units
+    insets: [f32; 4],
+    image_size: Vec2,
+    /// left, right, bottom and top insets of the slices in image pixels
+    image_insets: [f32; 4],
+}
+
+impl NineSlice {
+    /// The rounded mask stretched over `area` with corners of the given `radius`
+    fn rounded(area: Rect, radius: f32) -> Self {
+        Self {
+            area,
+            insets: [radius; 4],
+            image_size: mask_size(),
+            image_insets: [MASK_RADIUS; 4],
+        }
+    }
+}
+
+/// The start and end of each of `count` equally sized cells, with `gap` between neighbouring
+/// cells, spread over a bar of the given `length`, as fractions of the bar's length
+fn cells(count: u32, gap: f32, length: f32) -> impl Iterator<Item = (f32, f32)> {
+    let count = count.max(1) as f32;
+    let gap = (gap / length.max(f32::EPSILON)).clamp(0., 1. / (count - 1.).max(1.));
+    let cell_length = (1. - gap * (count - 1.)) / count;
+    (0..count as usize).map(move |cell| {
+        let from = cell as f32 * (cell_length + gap);
+        (from, from + cell_length)
+    })
+}
+
+/// The part of `bar_rect` between `from` and `to`, measured along the bar
+/// from its empty end as fractions of its length
+fn section(bar_rect: Rect, vertical: bool, reverse: bool, from: f32, to: f32) -> Rect {
+    let axis = if vertical { 1 } else { 0 };
+    let (start, end) = (bar_rect.min[axis], bar_rect.max[axis]);
+    let length = end - start;
+    let mut section = bar_rect;
+    if reverse {
+        section.min[axis] = end - to * length;
+        section.max[axis] = end - from * length;
+    } else {
+        section.min[axis] = start + from * length;
+        section.max[axis] = start + to * length;
+    }
+    section
+}
+
+fn image_id(image: Option<&Handle<Image>>) -> AssetId<Image> {
+    image.map(Handle::id).unwrap_or_default()
+}
+
+/// The part of the fill image shown over the part of the bar between `from` and `to`,
+/// measured as in `section`. Returns `None` if the image hasn't loaded yet.
+fn fill_rect(
+    images: &Assets<Image>,
+    image: &Handle<Image>,
+    from: f32,
+    to: f32,
+    vertical: bool,
+    reverse: bool,
+) -> Option<Rect> {
+    let image_size = images.get(image)?.size_f32();
+    // image space has y increasing downwards, the opposite of the bar
+    let (from, to) = if vertical != reverse {
+        (1. - to, 1. - from)
+    } else {
+        (from, to)
+    };
+    let axis = if vertical { 1 } else { 0 };
+    let mut rect = Rect::from_corners(Vec2::ZERO, image_size);
+    rect.min[axis] = from * image_size[axis];
+    rect.max[axis] = to * image_size[axis];
+    Some(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    #[test]
+    fn cells_are_spread_evenly_with_gaps() {
+        // four cells 25 long with gaps of 10 between them
+        let cells: Vec<_> = cells(4, 10., 130.).collect();
+        let expected = [(0., 25.), (35., 60.), (70., 95.), (105., 130.)];
+        assert_eq!(cells.len(), expected.len());
+        for ((from, to), (expected_from, expected_to)) in cells.into_iter().zip(expected) {
+            assert!((from * 130. - expected_from).abs() < 1e-3);
+            assert!((to * 130. - expected_to).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn cells_without_room_for_the_gaps_shrink_to_nothing() {
+        assert_eq!(cells(0, 5., 100.).collect::<Vec<_>>(), [(0., 1.)]);
+        for (from, to) in cells(3, 100., 10.) {
+            assert!(to - from <= 1e-6);
+            assert!((0. ..=1.).contains(&from));
+        }
+    }
+
+    #[test]
+    fn fill_rect_crops_the_image_from_the_empty_end() {
+        let mut images = Assets::<Image>::default();
+        let image = images.add(Image::new_fill(
+            Extent3d {
+                width: 100,
+                height: 50,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255; 4],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ));
+        let rect = |vertical, reverse| fill_rect(&images, &image, 0.25, 0.5, vertical, reverse);
+        assert_eq!(rect(false, false), Some(Rect::new(25., 0., 50., 50.)));
+        assert_eq!(rect(false, true), Some(Rect::new(50., 0., 75., 50.)));
+        assert_eq!(rect(true, false), Some(Rect::new(0., 25., 100., 37.5)));
+        assert_eq!(rect(true, true), Some(Rect::new(0., 12.5, 100., 25.)));
+        assert_eq!(
+            fill_rect(&images, &Handle::default(), 0., 1., false, false),
+            None
+        );
+    }
+
+    #[test]
+    fn overflow_layers_ignore_values_that_are_not_above_one() {
+        let overflow = StatbarOverflow::<()>::wrapping([Color::WHITE]);
+        for value in [0.5, 1., f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(OverflowLayers::new(&overflow, value).is_none());
+        }
+    }
+
+    #[test]
+    fn overflow_layers_count_and_fill_the_top_layer() {
+        let overflow = StatbarOverflow::<()>::wrapping([Color::WHITE, Color::BLACK]);
+        let layers = OverflowLayers::new(&overflow, 3.25).unwrap();
+        assert_eq!(layers.count, 3);
+        assert!((layers.fill - 0.25).abs() < 1e-6);
+        assert_eq!(layers.top, Color::WHITE.to_linear());
+        assert_eq!(layers.below, Some(Color::BLACK.to_linear()));
+
+        let layers = OverflowLayers::new(&overflow, 1e30).unwrap();
+        assert_eq!(layers.count, MAX_OVERFLOW_LAYERS as u32);
+        assert_eq!(layers.fill, 1.);
+    }
+
+    #[test]
+    fn overflow_layers_without_wrap_stop_at_one_full_layer() {
+        let overflow = StatbarOverflow::<()>::new(Color::WHITE);
+        let layers = OverflowLayers::new(&overflow, 5.).unwrap();
+        assert_eq!(layers.count, 1);
+        assert_eq!(layers.fill, 1.);
+        assert!(layers.below.is_none());
+    }
 }
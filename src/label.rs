@@ -0,0 +1,306 @@
+use crate::extraction::DEFAULT_Z_DEPTH;
+use crate::*;
+use bevy::sprite::Anchor;
+use std::sync::Arc;
+
+/// How the numbers of a `StatbarLabel` are written
+#[derive(Clone, Default)]
+pub enum StatbarLabelFormat {
+    /// current and max values, e.g. "75/100"
+    #[default]
+    Fraction,
+    /// how far the current value is from the min value to the max value, e.g. "75%"
+    Percent,
+    /// only the current value, e.g. "75"
+    Current,
+    /// written by a function of the current, min and max values
+    Custom(Arc<dyn Fn(f32, f32, f32) -> String + Send + Sync>),
+}
+
+impl std::fmt::Debug for StatbarLabelFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fraction => write!(f, "Fraction"),
+            Self::Percent => write!(f, "Percent"),
+            Self::Current => write!(f, "Current"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl StatbarLabelFormat {
+    /// `formatter` is called with the current, min and max values
+    pub fn custom(formatter: impl Fn(f32, f32, f32) -> String + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(formatter))
+    }
+
+    pub fn format(&self, current: f32, min: f32, max: f32) -> String {
+        match self {
+            Self::Fraction => format!("{current:.0}/{max:.0}"),
+            Self::Percent => {
+                let range = max - min;
+                let percent = if range == 0. {
+                    0.
+                } else {
+                    100. * (current - min) / range
+                };
+                format!("{percent:.0}%")
+            }
+            Self::Current => format!("{current:.0}"),
+            Self::Custom(formatter) => formatter(current, min, max),
+        }
+    }
+}
+
+/// Where a `StatbarLabel` is placed relative to its bar
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarLabelAlignment {
+    /// centered on the bar
+    #[default]
+    Center,
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+/// Draws the numbers of the corresponding Statbar as text
+///
+/// The numbers are the `current`, `min` and `max` values of the Statbar,
+/// with a missing `min` treated as zero.
+/// The label is drawn by a separate `Text2d` entity that is kept in sync with the bar,
+/// and despawned with it. Needs the `labels` feature.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarLabel<T>
+where
+    T: 'static,
+{
+    #[reflect(ignore)]
+    pub format: StatbarLabelFormat,
+    /// the default handle uses bevy's default font
+    pub font: Handle<Font>,
+    pub font_size: f32,
+    pub color: Color,
+    pub alignment: StatbarLabelAlignment,
+    /// gap between the bar and a label placed outside of it
+    pub margin: f32,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarLabel<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            format: StatbarLabelFormat::default(),
+            font: Handle::default(),
+            font_size: 14.,
+            color: Color::WHITE,
+            alignment: StatbarLabelAlignment::default(),
+            margin: 4.,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarLabel<T>
+where
+    T: 'static,
+{
+    pub fn new(format: StatbarLabelFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_font(mut self, font: Handle<Font>, font_size: f32) -> Self {
+        self.font = font;
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: StatbarLabelAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+}
+
+/// Marks the text entity drawing the `StatbarLabel<T>` of `bar`.
+#[derive(Component)]
+pub(crate) struct StatbarLabelText<T: 'static> {
+    bar: Entity,
+    phantom: PhantomData<fn() -> T>,
+}
+
+/// Links a bar to the text entity drawing its `StatbarLabel<T>`.
+///
+/// Kept out of `StatbarLabel` so cloning a label onto another bar
+/// gives that bar a text entity of its own.
+#[derive(Component)]
+pub(crate) struct StatbarLabelLink<T: 'static> {
+    text: Entity,
+    phantom: PhantomData<fn() -> T>,
+}
+
+/// Spawns, moves and updates the text of each `StatbarLabel<T>`,
+/// and despawns the texts whose bars or labels are gone.
+#[allow(clippy::type_complexity)]
+pub(crate) fn update_statbar_labels<T: TypePath>(
+    mut commands: Commands,
+    depth: Option<Res<StatbarDepth>>,
+    type_visibility: Option<Res<StatbarTypeVisibility<T>>>,
+    label_query: Query<(
+        Entity,
+        &Statbar<T>,
+        &StatbarLabel<T>,
+        Option<&StatbarLabelLink<T>>,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&StatbarAutoHide<T>>,
+        Option<&StatbarShowOnFocus<T>>,
+    )>,
+    mut text_query: Query<
+        (
+            Entity,
+            &StatbarLabelText<T>,
+            &mut Text,
+            &mut Anchor,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut Visibility,
+        ),
+        Without<Statbar<T>>,
+    >,
+) {
+    text_query.iter().for_each(|(text_entity, link, ..)| {
+        let linked = label_query
+            .get(link.bar)
+            .is_ok_and(|(_, _, _, link, ..)| link.is_some_and(|link| link.text == text_entity));
+        if !linked {
+            commands.entity(text_entity).despawn();
+        }
+    });
+
+    let z = depth.map(|depth| depth.0).unwrap_or(DEFAULT_Z_DEPTH) + 4.;
+    let type_hidden = type_visibility.is_some_and(|visibility| !visibility.is_visible());
+    label_query.iter().for_each(
+        |(entity, bar, label, link, global_transform, inherited_visibility, auto_hide, focus)| {
+            let unfocused = focus.is_some_and(|focus| !focus.is_shown());
+            let shown = !type_hidden && !bar.hide && inherited_visibility.get() && !unfocused;
+            let opacity = auto_hide.map_or(1., |auto_hide| auto_hide.opacity);
+            let style = TextStyle {
+                font: label.font.clone(),
+                font_size: label.font_size,
+                color: label.color.with_alpha(label.color.alpha() * opacity),
+            };
+            let value = label
+                .format
+                .format(bar.current, bar.min.unwrap_or(0.), bar.max);
+
+            let bounds = bar_bounds(bar, global_transform);
+            let (position, anchor) = match label.alignment {
+                StatbarLabelAlignment::Center => (bounds.center(), Anchor::Center),
+                StatbarLabelAlignment::Above => (
+                    Vec2::new(bounds.center().x, bounds.max.y + label.margin),
+                    Anchor::BottomCenter,
+                ),
+                StatbarLabelAlignment::Below => (
+                    Vec2::new(bounds.center().x, bounds.min.y - label.margin),
+                    Anchor::TopCenter,
+                ),
+                StatbarLabelAlignment::Left => (
+                    Vec2::new(bounds.min.x - label.margin, bounds.center().y),
+                    Anchor::CenterRight,
+                ),
+                StatbarLabelAlignment::Right => (
+                    Vec2::new(bounds.max.x + label.margin, bounds.center().y),
+                    Anchor::CenterLeft,
+                ),
+            };
+            let transform = Transform::from_translation(position.extend(z));
+            let visibility = if shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+
+            // only drive a text entity that was spawned for this bar
+            let linked_text = link
+                .and_then(|link| text_query.get_mut(link.text).ok())
+                .filter(|(_, text_link, ..)| text_link.bar == entity);
+            match linked_text {
+                Some((
+                    _,
+                    _,
+                    mut text,
+                    mut text_anchor,
+                    mut text_transform,
+                    mut text_global,
+                    mut text_visibility,
+                )) => {
+                    let section = &text.sections[0];
+                    if section.value != value
+                        || section.style.font != style.font
+                        || section.style.font_size != style.font_size
+                        || section.style.color != style.color
+                    {
+                        *text = Text::from_section(value, style);
+                    }
+                    text_anchor.set_if_neq(anchor);
+                    text_transform.set_if_neq(transform);
+                    // the transforms are already propagated this frame
+                    text_global.set_if_neq(GlobalTransform::from(transform));
+                    text_visibility.set_if_neq(visibility);
+                }
+                None => {
+                    let text = commands
+                        .spawn((
+                            Text2dBundle {
+                                text: Text::from_section(value, style),
+                                text_anchor: anchor,
+                                transform,
+                                global_transform: GlobalTransform::from(transform),
+                                visibility,
+                                ..Default::default()
+                            },
+                            StatbarLabelText::<T> {
+                                bar: entity,
+                                phantom: PhantomData,
+                            },
+                        ))
+                        .id();
+                    commands.entity(entity).insert(StatbarLabelLink::<T> {
+                        text,
+                        phantom: PhantomData,
+                    });
+                }
+            }
+        },
+    );
+}
+
+/// Registers `StatbarLabel<T>` and adds the system drawing its text
+pub(crate) fn add_labels<T: TypePath>(app: &mut App) {
+    app.register_type::<StatbarLabel<T>>()
+        .configure_sets(
+            PostUpdate,
+            StatbarSystem::UpdateLabels
+                .after(StatbarSystem::UpdateEffects)
+                .after(bevy::transform::TransformSystem::TransformPropagate)
+                .before(bevy::text::update_text2d_layout)
+                .before(bevy::render::view::VisibilitySystems::VisibilityPropagate),
+        )
+        .add_systems(
+            PostUpdate,
+            update_statbar_labels::<T>.in_set(StatbarSystem::UpdateLabels),
+        );
+}
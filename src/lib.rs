@@ -1,17 +1,140 @@
 mod extraction;
+#[cfg(feature = "labels")]
+mod label;
+mod mask;
 
-use bevy::{prelude::*, reflect::TypePath};
+use bevy::{ecs::schedule::Condition, prelude::*, reflect::TypePath};
 use std::marker::PhantomData;
 
+#[cfg(feature = "labels")]
+pub use label::{StatbarLabel, StatbarLabelAlignment, StatbarLabelFormat};
+
 /// Insert as a resource to set z depth of Statbars
 #[derive(Resource)]
 pub struct StatbarDepth(pub f32);
 
+/// When the Statbars with a `StatbarTypeVisibility` are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarVisibilityMode {
+    /// draw the statbars as normal
+    #[default]
+    Always,
+    /// don't draw any of the statbars
+    Never,
+    /// only draw the statbars while the condition registered with `show_statbars_while` holds
+    WhileCondition,
+}
+
+/// Insert as a resource to show or hide every `Statbar<T>` at once,
+/// without touching their components
+///
+/// Could be used for a key that shows all the health bars while it's held down,
+/// see `RegisterStatbarSubject::show_statbars_while`.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct StatbarTypeVisibility<T>
+where
+    T: 'static,
+{
+    pub mode: StatbarVisibilityMode,
+    /// if true, the statbars aren't drawn whatever the mode
+    pub hidden: bool,
+    /// whether the condition held when last checked
+    #[reflect(ignore)]
+    pub(crate) condition_met: bool,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarTypeVisibility<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(StatbarVisibilityMode::Always)
+    }
+}
+
+impl<T> StatbarTypeVisibility<T>
+where
+    T: 'static,
+{
+    pub fn new(mode: StatbarVisibilityMode) -> Self {
+        Self {
+            mode,
+            hidden: false,
+            condition_met: false,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Whether the statbars are drawn
+    pub fn is_visible(&self) -> bool {
+        !self.hidden
+            && match self.mode {
+                StatbarVisibilityMode::Always => true,
+                StatbarVisibilityMode::Never => false,
+                StatbarVisibilityMode::WhileCondition => self.condition_met,
+            }
+    }
+
+    /// Hide the statbars, or show them again as the mode says.
+    ///
+    /// Only flips `hidden`, so a `WhileCondition` mode keeps following its condition once
+    /// the statbars are shown again. A `Never` mode is switched to `Always` to show them.
+    pub fn toggle(&mut self) {
+        if self.mode == StatbarVisibilityMode::Never {
+            self.mode = StatbarVisibilityMode::Always;
+            self.hidden = false;
+        } else {
+            self.hidden = !self.hidden;
+        }
+    }
+}
+
 /// Implement `StatbarObservable` for a component you want to visualise with a stat bar.
-/// Should return a value between 0.0 (= empty) and 1.0 (= full).
-/// If the value is larger or smaller it is clamped before rendering.
+/// The normalized value should be between 0.0 (= empty) and 1.0 (= full).
+/// If the value is larger or smaller it is clamped before rendering,
+/// unless the Statbar has a `StatbarOverflow` to show values above 1.0.
+///
+/// Implement either `get_statbar_value`, or `get_statbar_current` with `get_statbar_max`
+/// and optionally `get_statbar_min` to have the normalized value derived from them.
+/// Each of the two defaults to the other, so implementing neither recurses forever.
+///
+/// The absolute numbers are stored on the Statbar and used by `StatbarLabel`,
+/// `StatbarSegmentation::ObservedAmount` and `StatbarIcons::per_observed_amount`.
 pub trait StatbarObservable {
-    fn get_statbar_value(&self) -> f32;
+    /// The normalized value, by default how far the current value is from the min value
+    /// to the max value
+    fn get_statbar_value(&self) -> f32 {
+        let min = self.get_statbar_min().unwrap_or(0.);
+        let range = self.get_statbar_max() - min;
+        if range == 0. {
+            0.
+        } else {
+            (self.get_statbar_current() - min) / range
+        }
+    }
+
+    /// The absolute current value, the normalized value by default
+    fn get_statbar_current(&self) -> f32 {
+        self.get_statbar_value()
+    }
+
+    /// The absolute maximum value, 1.0 by default
+    fn get_statbar_max(&self) -> f32 {
+        1.
+    }
+
+    /// The absolute minimum value, if it isn't zero
+    fn get_statbar_min(&self) -> Option<f32> {
+        None
+    }
+
+    /// A secondary value of the stat, like a shield or a value about to be restored
+    fn get_statbar_secondary(&self) -> Option<f32> {
+        None
+    }
 }
 
 /// Insert this component to observe components from another entity.
@@ -53,6 +176,127 @@ where
     }
 }
 
+/// The outline of a Statbar or StatbarBorder
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum StatbarShape {
+    /// sharp cornered rectangle
+    #[default]
+    Rectangle,
+    /// rectangle with corners rounded to the given radius,
+    /// the radius is limited to half the thickness of the bar
+    Rounded(f32),
+    /// rectangle with fully rounded ends
+    Capsule,
+}
+
+impl StatbarShape {
+    /// The corner radius of the shape when drawn with the given size
+    pub fn radius(self, size: Vec2) -> f32 {
+        let max_radius = 0.5 * size.min_element();
+        match self {
+            StatbarShape::Rectangle => 0.,
+            StatbarShape::Rounded(radius) => radius.clamp(0., max_radius),
+            StatbarShape::Capsule => max_radius,
+        }
+    }
+}
+
+/// Draws a Statbar as a ring or an arc instead of a straight bar
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct StatbarRadial {
+    /// angle in radians the arc starts at, measured anticlockwise from the positive x axis
+    pub start_angle: f32,
+    /// angle in radians the full arc covers, `TAU` for a complete ring
+    pub sweep: f32,
+    /// distance from the center to the inside edge of the arc
+    pub inner_radius: f32,
+    /// distance from the center to the outside edge of the arc
+    pub outer_radius: f32,
+    /// true => the arc fills clockwise from `start_angle`,
+    /// false => the arc fills anticlockwise from `start_angle`
+    pub clockwise: bool,
+}
+
+impl Default for StatbarRadial {
+    fn default() -> Self {
+        Self {
+            start_angle: std::f32::consts::FRAC_PI_2,
+            sweep: std::f32::consts::TAU,
+            inner_radius: 20.,
+            outer_radius: 28.,
+            clockwise: true,
+        }
+    }
+}
+
+impl StatbarRadial {
+    /// A complete ring starting at the top and filling clockwise
+    pub fn ring(inner_radius: f32, outer_radius: f32) -> Self {
+        Self {
+            inner_radius,
+            outer_radius,
+            ..Default::default()
+        }
+    }
+
+    /// The signed angle covered by the arc when filled to `value`
+    pub fn angle(&self, value: f32) -> f32 {
+        let angle = self.sweep * value.clamp(0., 1.);
+        if self.clockwise {
+            -angle
+        } else {
+            angle
+        }
+    }
+}
+
+/// Draws a Statbar as a row of separate boxes, or pips, instead of a continuous bar
+///
+/// The value is split between the pips, each pip is full, empty or partially filled.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct StatbarPips {
+    /// number of pips
+    pub count: u32,
+    /// space between neighbouring pips
+    pub gap: f32,
+}
+
+impl Default for StatbarPips {
+    fn default() -> Self {
+        Self { count: 5, gap: 2. }
+    }
+}
+
+impl StatbarPips {
+    pub fn new(count: u32, gap: f32) -> Self {
+        Self { count, gap }
+    }
+}
+
+/// Draws a Statbar that fills outwards from its center, for values between -1.0 and 1.0
+///
+/// Positive values fill towards the end a normal bar fills towards with the bar's color,
+/// negative values fill towards the other end with `negative_color`.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct StatbarBipolar {
+    /// color of the full part of the bar when the value is negative
+    pub negative_color: Color,
+}
+
+impl Default for StatbarBipolar {
+    fn default() -> Self {
+        Self {
+            negative_color: Color::from(bevy::color::palettes::css::RED),
+        }
+    }
+}
+
+impl StatbarBipolar {
+    pub fn new(negative_color: Color) -> Self {
+        Self { negative_color }
+    }
+}
+
 /// Insert this component to add a statbar to an entity.
 /// Multiple statbars can be inserted on a single entity by using different marker components.
 #[derive(Component, Reflect)]
@@ -61,10 +305,13 @@ pub struct Statbar<T = ()>
 where
     T: TypePath + 'static,
 {
-    /// color of the full part of the bar
+    /// base color of the full part of the bar
     pub color: Color,
     /// color of the empty part of the bar
     pub empty_color: Color,
+    /// color the full part of the bar is drawn with,
+    /// rebuilt from `color` every frame by the `StatbarColorSystem` stages
+    pub display_color: Color,
     /// length of the bar
     pub length: f32,
     /// thickness of the bar
@@ -78,17 +325,44 @@ where
     /// false =>
     /// * horizontal bar increasing from left to right
     /// * vertical bar increasing from bottom to top
+    ///
     /// true =>
     /// * horizontal bar increasing from right to left
-    /// * vertical bar increasing from bottom to top
+    /// * vertical bar increasing from top to bottom
     pub reverse: bool,
+    /// outline of the bar, the full part of the bar is clipped to the same outline.
+    /// Ignored for parts of the bar drawn with a `StatbarTexture` image.
+    pub shape: StatbarShape,
+    /// if set, draw the bar as a ring or arc.
+    /// `length`, `thickness`, `vertical`, `reverse`, `shape` and any
+    /// `StatbarTexture` are ignored for radial bars.
+    pub radial: Option<StatbarRadial>,
+    /// if set, draw the bar as a row of pips spread over its length.
+    /// `shape` applies to each pip, any `StatbarTexture` fill and empty images are ignored.
+    pub pips: Option<StatbarPips>,
+    /// if set, the bar fills outwards from its center and `value` ranges from -1.0 to 1.0.
+    /// Ignored for radial, pip and icon bars.
+    pub bipolar: Option<StatbarBipolar>,
     /// if true, do not draw
     pub hide: bool,
     /// value of bar
     /// * 0.0 => bar entirely colored with empty color
     /// * 0.75 => bar three quarters full color, one quarter empty color
     /// * 1.0 => bar entity colored with full color
+    ///
+    /// for a bipolar bar
+    /// * -1.0 => bar half empty, with the other half colored with the negative color
+    /// * 0.0 => bar entirely colored with empty color
+    /// * 1.0 => bar half empty, with the other half colored with full color
     pub value: f32,
+    /// absolute current value reported by the observed component
+    pub current: f32,
+    /// absolute maximum value reported by the observed component
+    pub max: f32,
+    /// absolute minimum value reported by the observed component, if it isn't zero
+    pub min: Option<f32>,
+    /// secondary value reported by the observed component
+    pub secondary: Option<f32>,
     #[reflect(ignore)]
     #[doc(hidden)]
     pub _phantom: PhantomData<fn() -> T>,
@@ -99,19 +373,57 @@ impl<T: TypePath> Default for Statbar<T> {
         Self {
             color: Color::from(bevy::color::palettes::css::YELLOW),
             empty_color: Color::srgb(0.2, 0.2, 0.0),
+            display_color: Color::from(bevy::color::palettes::css::YELLOW),
             length: 100.,
             thickness: 16.,
             displacement: Vec2::ZERO,
             vertical: false,
             reverse: false,
+            shape: StatbarShape::Rectangle,
+            radial: None,
+            pips: None,
+            bipolar: None,
             hide: false,
             value: 0.75,
+            current: 0.75,
+            max: 1.,
+            min: None,
+            secondary: None,
             _phantom: PhantomData,
         }
     }
 }
 
+impl<T: TypePath> Statbar<T> {
+    /// Copy the normalized value and the absolute numbers from `observed`
+    pub fn observe(&mut self, observed: &(impl StatbarObservable + ?Sized)) {
+        self.value = observed.get_statbar_value();
+        self.current = observed.get_statbar_current();
+        self.max = observed.get_statbar_max();
+        self.min = observed.get_statbar_min();
+        self.secondary = observed.get_statbar_secondary();
+    }
+
+    /// The value remapped from -1.0..1.0 to 0.0..1.0 for a bipolar bar,
+    /// so the negative end of the bar is 0.0
+    pub fn unipolar_value(&self) -> f32 {
+        if self.bipolar.is_some() {
+            0.5 * (self.value + 1.)
+        } else {
+            self.value
+        }
+    }
+}
+
 /// Adds a border around the corresponding Statbar
+///
+/// Around a radial bar, `bottom` extends the inside edge of the arc, `top` the outside edge,
+/// and `left` and `right` the ends of the arc.
+///
+/// With `nine_slice` set and a border image in the Statbar's `StatbarTexture`, the image is
+/// drawn as a nine-slice frame. The image is sliced at `image_insets` pixels from each side,
+/// or at the thickness of each side if unset. The corners are drawn to the thickness of
+/// their sides and the edges and center stretched to fit the bar.
 #[derive(Clone, Copy, Debug, Component, Reflect)]
 #[reflect(Component)]
 pub struct StatbarBorder<T>
@@ -128,6 +440,13 @@ where
     bottom: f32,
     /// thickness of the border on the top
     top: f32,
+    /// draw the border image as a nine-slice frame
+    pub nine_slice: bool,
+    /// pixels from the left, right, bottom and top edges of the border image at which it is sliced.
+    /// None => sliced at the thickness of each side
+    pub image_insets: Option<[f32; 4]>,
+    /// outline of the border, ignored if the border is drawn with an image
+    pub shape: StatbarShape,
     #[reflect(ignore)]
     phantom: PhantomData<fn() -> T>,
 }
@@ -138,15 +457,43 @@ where
 {
     /// A StarbarBorder with the same thickness on all four sides
     pub fn all(color: Color, thickness: f32) -> Self {
+        Self::new(color, thickness, thickness, thickness, thickness)
+    }
+
+    /// A StarbarBorder with the given thickness on each side
+    pub fn new(color: Color, left: f32, right: f32, bottom: f32, top: f32) -> Self {
         Self {
             color,
-            left: thickness,
-            right: thickness,
-            bottom: thickness,
-            top: thickness,
+            left,
+            right,
+            bottom,
+            top,
+            nine_slice: false,
+            image_insets: None,
+            shape: StatbarShape::Rectangle,
             phantom: PhantomData,
         }
     }
+
+    /// Draw the border image as a nine-slice frame
+    pub fn with_nine_slice(mut self) -> Self {
+        self.nine_slice = true;
+        self
+    }
+
+    /// Draw the border image as a nine-slice frame sliced at the given pixels from its
+    /// left, right, bottom and top edges, e.g. at the 16 pixel corners of the art
+    pub fn with_image_insets(mut self, left: f32, right: f32, bottom: f32, top: f32) -> Self {
+        self.nine_slice = true;
+        self.image_insets = Some([left, right, bottom, top]);
+        self
+    }
+
+    /// Draw the border with the given outline
+    pub fn with_shape(mut self, shape: StatbarShape) -> Self {
+        self.shape = shape;
+        self
+    }
 }
 
 impl<T> Default for StatbarBorder<T>
@@ -158,135 +505,1938 @@ where
     }
 }
 
-/// Linearly interpolate the value of the bar color
-/// between `min` and `max` using the value of the Statbar
-/// * statbar.value == 0. => statbar.color == min
-/// * statbar.value == 1. => statbar.color == max
+/// How a `StatbarSegments` divides its Statbar
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum StatbarSegmentation {
+    /// divide the bar into this many equal segments
+    Count(u32),
+    /// one segment for every `amount` of a stat with the given maximum,
+    /// e.g. one segment per 100 hit points. The last segment is shorter if `max`
+    /// isn't a multiple of `amount`.
+    Amount { amount: f32, max: f32 },
+    /// one segment for every `amount` of the Statbar's observed `max`,
+    /// following the maximum as it changes
+    ObservedAmount(f32),
+}
+
+/// Upper limit on the number of segments of a `StatbarSegments`, no separators are drawn past it.
+const MAX_STATBAR_SEGMENTS: f32 = 1000.;
+
+impl StatbarSegmentation {
+    /// The number of segments, the last segment is partial if this isn't a whole number
+    fn segments(self, observed_max: f32) -> f32 {
+        let per_amount = |amount: f32, max: f32| if 0. < amount { max / amount } else { 0. };
+        match self {
+            StatbarSegmentation::Count(count) => count as f32,
+            StatbarSegmentation::Amount { amount, max } => per_amount(amount, max),
+            StatbarSegmentation::ObservedAmount(amount) => per_amount(amount, observed_max),
+        }
+    }
+
+    /// Positions of the separators between segments, as fractions of the bar's length
+    ///
+    /// `observed_max` is the Statbar's `max`, only used by `ObservedAmount`.
+    /// There are no separators for more than 1000 segments.
+    pub fn separators(self, observed_max: f32) -> impl Iterator<Item = f32> {
+        let segments = self.segments(observed_max);
+        let count = if segments <= MAX_STATBAR_SEGMENTS {
+            // no separator on the end of the bar, allowing for rounding errors in `segments`
+            ((segments * (1. - 1e-5)).ceil() - 1.).max(0.) as u32
+        } else {
+            0
+        };
+        (1..=count).map(move |separator| separator as f32 / segments)
+    }
+}
+
+/// Divides the corresponding Statbar into segments with separator lines drawn over the bar
+///
+/// Could be used to make large health pools readable by counting the segments.
 #[derive(Clone, Copy, Debug, Component, Reflect)]
 #[reflect(Component)]
-pub struct StatbarColorLerp<T>
+pub struct StatbarSegments<T>
 where
     T: 'static,
 {
-    /// bar color when value is 0.0
-    pub min: Color,
-    /// bar color when value is 1.0
-    pub max: Color,
+    pub segmentation: StatbarSegmentation,
+    /// color of the separator lines
+    pub color: Color,
+    /// width of the separator lines
+    pub width: f32,
     #[reflect(ignore)]
     phantom: PhantomData<fn() -> T>,
 }
 
-impl<T> Default for StatbarColorLerp<T>
+impl<T> Default for StatbarSegments<T>
 where
     T: 'static,
 {
     fn default() -> Self {
+        Self::new(StatbarSegmentation::Count(4))
+    }
+}
+
+impl<T> StatbarSegments<T>
+where
+    T: 'static,
+{
+    /// Black separator lines one unit wide
+    pub fn new(segmentation: StatbarSegmentation) -> Self {
         Self {
-            min: Color::from(bevy::color::palettes::css::RED),
-            max: Color::from(bevy::color::palettes::css::GREEN),
+            segmentation,
+            color: Color::BLACK,
+            width: 1.,
             phantom: Default::default(),
         }
     }
+
+    /// `count` equal segments
+    pub fn count(count: u32) -> Self {
+        Self::new(StatbarSegmentation::Count(count))
+    }
+
+    /// one segment for every `amount` of a stat with maximum `max`
+    pub fn per_amount(amount: f32, max: f32) -> Self {
+        Self::new(StatbarSegmentation::Amount { amount, max })
+    }
+
+    /// one segment for every `amount` of the Statbar's observed `max`
+    pub fn per_observed_amount(amount: f32) -> Self {
+        Self::new(StatbarSegmentation::ObservedAmount(amount))
+    }
+
+    /// Separator lines with the given color and width
+    pub fn with_separators(mut self, color: Color, width: f32) -> Self {
+        self.color = color;
+        self.width = width;
+        self
+    }
+
+    /// The separators drawn along a bar `length` long,
+    /// none if they would be closer together than their width
+    pub(crate) fn separators(&self, observed_max: f32, length: f32) -> impl Iterator<Item = f32> {
+        let crowded = length < self.segmentation.segments(observed_max) * self.width;
+        self.segmentation
+            .separators(observed_max)
+            .filter(move |_| !crowded)
+    }
 }
 
-impl<T> StatbarColorLerp<T>
+/// Upper limit on the number of icons drawn by a `StatbarIcons` following the observed max.
+const MAX_STATBAR_ICONS: f32 = 100.;
+
+/// Draws the corresponding Statbar as a row of repeated icons, like heart containers
+///
+/// The value is split between the icons, each icon is full, empty or partially filled.
+/// A partially filled icon is cut in the direction the bar fills, either at the exact fraction
+/// or rounded down to a number of `steps`, e.g. 4 for quarter hearts.
+/// The icons are tinted with the Statbar's colors, set them to `Color::WHITE` to draw the
+/// images unchanged. Not drawn for radial bars.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarIcons<T>
 where
     T: 'static,
 {
-    pub fn new(min: Color, max: Color) -> Self {
+    /// image of a full icon
+    pub full: Handle<Image>,
+    /// image drawn behind partial and empty icons
+    pub empty: Option<Handle<Image>>,
+    /// number of icons
+    pub count: u32,
+    /// if set, one icon for every this much of the Statbar's observed `max`
+    /// instead of `count` icons, e.g. one heart per 20 hit points. At most 100 icons are drawn,
+    /// each standing for a larger amount if the max is higher
+    pub per_amount: Option<f32>,
+    /// space between neighbouring icons
+    pub gap: f32,
+    /// if set, partial icons are only drawn in this many steps
+    pub steps: Option<u32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StatbarIcons<T>
+where
+    T: 'static,
+{
+    /// `count` icons with no gaps between them
+    pub fn new(full: Handle<Image>, empty: Handle<Image>, count: u32) -> Self {
         Self {
-            min,
-            max,
+            full,
+            empty: Some(empty),
+            count,
+            per_amount: None,
+            gap: 0.,
+            steps: None,
             phantom: Default::default(),
         }
     }
+
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Only draw partial icons in `steps` steps
+    pub fn with_steps(mut self, steps: u32) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    /// One icon for every `amount` of the Statbar's observed `max`
+    pub fn per_observed_amount(mut self, amount: f32) -> Self {
+        self.per_amount = Some(amount);
+        self
+    }
+
+    /// The number of icons drawn, and how many icons `value` fills
+    pub(crate) fn fill(&self, value: f32, observed_max: f32) -> (u32, f32) {
+        let value = value.clamp(0., 1.);
+        match self.per_amount.filter(|&amount| 0. < amount) {
+            Some(amount) => {
+                let icons = (observed_max / amount).min(MAX_STATBAR_ICONS);
+                (icons.ceil().max(1.) as u32, value * icons)
+            }
+            None => {
+                let count = self.count.max(1);
+                (count, value * count as f32)
+            }
+        }
+    }
 }
 
-/// Change the statbar color depending on the value of the statbar's subject
+/// Shows the part of the value of the corresponding Statbar above 1.0,
+/// drawn as further layers of fill over the full bar
 ///
-/// Could be used for a health bar that
-/// turns to red when the character has less than 25% health remaining.
-#[derive(Clone, Copy, Debug, Component, Reflect)]
+/// Could be used to show overheal on a health bar.
+/// The first overflow layer is drawn for values between 1.0 and 2.0,
+/// the second between 2.0 and 3.0, and so on.
+/// Not drawn for bipolar, pip or icon bars.
+#[derive(Clone, Debug, Component, Reflect)]
 #[reflect(Component)]
-pub struct StatbarColorSwitch<T>
+pub struct StatbarOverflow<T>
 where
     T: 'static,
 {
-    /// * `statbar.value <= pivot`
-    ///     => sets bar's color to `low`
-    /// * `piviot < statbar.value
-    ///     => sets bar's color to `high`
-    pub pivot: f32,
-    /// statbar color when the statbar's value is less than or equal to pivot
-    pub low: Color,
-    /// statbar color when the statbar's value is greater than pivot
-    pub high: Color,
+    /// colors of the overflow layers, repeating if there are more layers than colors
+    pub colors: Vec<Color>,
+    /// false => values above 2.0 are drawn as a single full overflow layer,
+    /// true => the overflow wraps around the bar again for each extra layer, up to 1000 layers
+    pub wrap: bool,
+    /// if set, draws a marker of this color for each overflow layer past the full end of the bar,
+    /// once the overflow has wrapped, up to 10 markers. Not drawn for radial bars.
+    pub marker_color: Option<Color>,
     #[reflect(ignore)]
     phantom: PhantomData<fn() -> T>,
 }
 
-impl<T> Default for StatbarColorSwitch<T>
+impl<T> Default for StatbarOverflow<T>
 where
     T: 'static,
 {
     fn default() -> Self {
+        Self::new(Color::from(bevy::color::palettes::css::AQUA))
+    }
+}
+
+impl<T> StatbarOverflow<T>
+where
+    T: 'static,
+{
+    /// A single overflow layer with the given color
+    pub fn new(color: Color) -> Self {
         Self {
-            pivot: 0.25,
-            low: Color::from(bevy::color::palettes::css::RED),
-            high: Color::from(bevy::color::palettes::css::GREEN),
+            colors: vec![color],
+            wrap: false,
+            marker_color: None,
             phantom: Default::default(),
         }
     }
+
+    /// Wrap the overflow around the bar again for each extra layer, cycling through `colors`
+    pub fn wrapping(colors: impl IntoIterator<Item = Color>) -> Self {
+        Self {
+            colors: colors.into_iter().collect(),
+            wrap: true,
+            ..Default::default()
+        }
+    }
+
+    /// Draw a marker for each overflow layer once the overflow has wrapped
+    pub fn with_markers(mut self, color: Color) -> Self {
+        self.marker_color = Some(color);
+        self
+    }
 }
 
-impl<T> StatbarColorSwitch<T>
+/// One segment of a `StatbarStack`
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub struct StatbarStackSegment {
+    /// color of the segment
+    pub color: Color,
+    /// length of the segment as a fraction of the bar's length, like `Statbar::value`
+    pub value: f32,
+}
+
+impl StatbarStackSegment {
+    pub fn new(color: Color) -> Self {
+        Self { color, value: 0. }
+    }
+}
+
+/// Adds further segments to the full part of the corresponding Statbar, drawn end to end after it
+///
+/// Could be used for a bar showing health followed by shield followed by temporary hit points.
+/// If the Statbar's value and the segments add up to more than 1.0 they are all shrunk in
+/// proportion to fit the bar.
+///
+/// Set the values of the segments yourself or register a stat for each segment with
+/// `add_statbar_stack_observer`, which measures the stat against the Statbar's max,
+/// so a full 100 health bar followed by a full 50 shield segment are drawn at 2:1.
+/// Only drawn for continuous, non-bipolar, non-radial bars.
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarStack<T>
 where
     T: 'static,
 {
-    pub fn new(pivot: f32, low: Color, high: Color) -> Self {
+    /// segments in the order they are drawn after the Statbar's full part
+    pub segments: Vec<StatbarStackSegment>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StatbarStack<T>
+where
+    T: 'static,
+{
+    /// Empty segments with the given colors
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
         Self {
-            pivot,
-            low,
-            high,
+            segments: colors.into_iter().map(StatbarStackSegment::new).collect(),
             phantom: Default::default(),
         }
     }
+
+    /// The sum of the values of all the segments, ignoring negative values
+    pub fn total(&self) -> f32 {
+        self.segments
+            .iter()
+            .map(|segment| segment.value.max(0.))
+            .sum()
+    }
 }
 
-#[allow(clippy::type_complexity)]
-fn switch_stat_bar_colors<T>(
-    mut color_switch_query: Query<
-        (&mut Statbar<T>, &mut StatbarColorSwitch<T>),
-        Changed<Statbar<T>>,
-    >,
-) where
-    T: 'static + TypePath,
+/// Previews a pending change to the value of the corresponding Statbar,
+/// drawn as a segment at the edge of the bar's full part
+///
+/// Could be used to show the mana a spell would cost or the health a heal would restore
+/// before the player confirms the action.
+/// A gain extends the full part of the bar, a cost is drawn over the end of it.
+/// Not drawn for bipolar, pip or icon bars.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarPreview<T>
+where
+    T: 'static,
 {
-    color_switch_query
-        .iter_mut()
-        .for_each(|(mut bar, switcher)| {
-            bar.color = if bar.value <= switcher.pivot {
-                switcher.low
-            } else {
-                switcher.high
-            };
-        });
+    /// the previewed change to the value, positive for a gain and negative for a cost.
+    /// 0.0 => nothing is drawn
+    pub amount: f32,
+    /// color of the segment when `amount` is positive
+    pub gain_color: Color,
+    /// color of the segment when `amount` is negative
+    pub cost_color: Color,
+    /// if set, the segment pulses this many times a second
+    pub pulse: Option<f32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
 }
 
-#[allow(clippy::type_complexity)]
-fn lerp_stat_bar_colors<T>(
-    mut color_lerp_query: Query<(&mut Statbar<T>, &mut StatbarColorLerp<T>), Changed<Statbar<T>>>,
-) where
-    T: 'static + TypePath,
+impl<T> Default for StatbarPreview<T>
+where
+    T: 'static,
 {
-    color_lerp_query.iter_mut().for_each(|(mut bar, lerper)| {
-        bar.color = lerper.min.mix(&lerper.max, bar.value);
-    });
+    fn default() -> Self {
+        Self {
+            amount: 0.,
+            gain_color: Color::WHITE,
+            cost_color: Color::from(bevy::color::palettes::css::DARK_RED),
+            pulse: None,
+            phantom: Default::default(),
+        }
+    }
 }
 
-#[allow(clippy::type_complexity)]
-fn update_statbar_values<T>(
-    mut statbar_query: Query<
+impl<T> StatbarPreview<T>
+where
+    T: 'static,
+{
+    pub fn new(amount: f32) -> Self {
+        Self {
+            amount,
+            ..Default::default()
+        }
+    }
+
+    /// Pulse the segment `frequency` times a second
+    pub fn with_pulse(mut self, frequency: f32) -> Self {
+        self.pulse = Some(frequency);
+        self
+    }
+}
+
+/// Easing curves for `StatbarTween`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarEasing {
+    Linear,
+    QuadraticIn,
+    #[default]
+    QuadraticOut,
+    QuadraticInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+}
+
+impl StatbarEasing {
+    /// Eases `t` between 0.0 and 1.0
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            StatbarEasing::Linear => t,
+            StatbarEasing::QuadraticIn => t * t,
+            StatbarEasing::QuadraticOut => 1. - (1. - t).powi(2),
+            StatbarEasing::QuadraticInOut => {
+                if t < 0.5 {
+                    2. * t * t
+                } else {
+                    1. - 0.5 * (2. - 2. * t).powi(2)
+                }
+            }
+            StatbarEasing::CubicIn => t.powi(3),
+            StatbarEasing::CubicOut => 1. - (1. - t).powi(3),
+            StatbarEasing::CubicInOut => {
+                if t < 0.5 {
+                    4. * t.powi(3)
+                } else {
+                    1. - 0.5 * (2. - 2. * t).powi(3)
+                }
+            }
+            StatbarEasing::SineInOut => 0.5 - 0.5 * (std::f32::consts::PI * t).cos(),
+        }
+    }
+}
+
+/// Moves the value of the corresponding Statbar smoothly to each newly observed value,
+/// instead of it jumping there instantly
+///
+/// Stops regeneration ticks and hits making the bar stutter.
+/// Any change to `Statbar::value` made during `StatbarSystem::UpdateValues` is treated as a new
+/// target, tweened to from the currently displayed value.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarTween<T>
+where
+    T: 'static,
+{
+    /// seconds taken to reach a new target
+    pub duration: f32,
+    pub easing: StatbarEasing,
+    /// false => follow `Time<Virtual>`, pausing and scaling with the game,
+    /// true => follow `Time<Real>`
+    pub real_time: bool,
+    /// value currently displayed by the bar
+    #[reflect(ignore)]
+    current: Option<f32>,
+    #[reflect(ignore)]
+    start: f32,
+    #[reflect(ignore)]
+    target: f32,
+    #[reflect(ignore)]
+    elapsed: f32,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarTween<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(0.25, StatbarEasing::default())
+    }
+}
+
+impl<T> StatbarTween<T>
+where
+    T: 'static,
+{
+    pub fn new(duration: f32, easing: StatbarEasing) -> Self {
+        Self {
+            duration,
+            easing,
+            real_time: false,
+            current: None,
+            start: 0.,
+            target: 0.,
+            elapsed: 0.,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Follow real time instead of virtual time
+    pub fn in_real_time(mut self) -> Self {
+        self.real_time = true;
+        self
+    }
+
+    /// The value the bar is moving towards
+    pub(crate) fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// The value to display after `delta_seconds`,
+    /// given the value of the bar after the values were updated
+    fn update(&mut self, value: f32, delta_seconds: f32) -> f32 {
+        let Some(current) = self.current else {
+            // nothing to tween from yet
+            self.current = Some(value);
+            self.target = value;
+            self.elapsed = self.duration;
+            return value;
+        };
+        if value != current {
+            self.start = current;
+            self.target = value;
+            self.elapsed = 0.;
+        }
+        self.elapsed += delta_seconds;
+        let t = if 0. < self.duration {
+            self.elapsed / self.duration
+        } else {
+            1.
+        };
+        let current = self.start + (self.target - self.start) * self.easing.ease(t);
+        self.current = Some(current);
+        current
+    }
+}
+
+/// Sets the length of the corresponding Statbar from its observed `max`,
+/// so the bar grows as the stat's maximum grows
+///
+/// The length is `per_point` times the maximum, kept between `min_length` and `max_length`.
+/// Not used by radial bars.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarLengthFromMax<T>
+where
+    T: 'static,
+{
+    /// length of the bar for each point of the maximum
+    pub per_point: f32,
+    pub min_length: f32,
+    pub max_length: f32,
+    /// seconds taken to grow or shrink to a new length, 0.0 => instantly
+    pub duration: f32,
+    pub easing: StatbarEasing,
+    /// length currently drawn
+    #[reflect(ignore)]
+    current: Option<f32>,
+    #[reflect(ignore)]
+    start: f32,
+    #[reflect(ignore)]
+    target: f32,
+    #[reflect(ignore)]
+    elapsed: f32,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StatbarLengthFromMax<T>
+where
+    T: 'static,
+{
+    /// Unlimited length, changing instantly
+    pub fn new(per_point: f32) -> Self {
+        Self {
+            per_point,
+            min_length: 0.,
+            max_length: f32::INFINITY,
+            duration: 0.,
+            easing: StatbarEasing::default(),
+            current: None,
+            start: 0.,
+            target: 0.,
+            elapsed: 0.,
+            phantom: Default::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, min_length: f32, max_length: f32) -> Self {
+        self.min_length = min_length;
+        self.max_length = max_length;
+        self
+    }
+
+    /// Grow or shrink to a new length over `duration` seconds
+    pub fn animated(mut self, duration: f32, easing: StatbarEasing) -> Self {
+        self.duration = duration;
+        self.easing = easing;
+        self
+    }
+
+    /// The length to draw after `delta_seconds`, given the observed maximum
+    fn update(&mut self, max: f32, delta_seconds: f32) -> f32 {
+        let target = (max * self.per_point)
+            .min(self.max_length)
+            .max(self.min_length);
+        let Some(current) = self.current else {
+            // start at the full length instead of growing from nothing
+            self.current = Some(target);
+            self.start = target;
+            self.target = target;
+            return target;
+        };
+        if target != self.target {
+            self.start = current;
+            self.target = target;
+            self.elapsed = 0.;
+        }
+        self.elapsed += delta_seconds;
+        let t = if 0. < self.duration {
+            self.elapsed / self.duration
+        } else {
+            1.
+        };
+        let current = self.start + (self.target - self.start) * self.easing.ease(t);
+        self.current = Some(current);
+        current
+    }
+}
+
+/// Which changes in the value of a Statbar trigger an effect
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarTrigger {
+    /// the value decreases
+    #[default]
+    Drop,
+    /// the value increases
+    Rise,
+    /// the value decreases or increases
+    Either,
+}
+
+impl StatbarTrigger {
+    /// Whether the change from `previous` to `value` is large enough
+    /// and in the right direction to trigger
+    pub fn is_triggered(self, previous: f32, value: f32, min_delta: f32) -> bool {
+        let delta = value - previous;
+        if delta.abs() < min_delta.max(f32::EPSILON) {
+            return false;
+        }
+        match self {
+            StatbarTrigger::Drop => delta < 0.,
+            StatbarTrigger::Rise => 0. < delta,
+            StatbarTrigger::Either => true,
+        }
+    }
+}
+
+/// Briefly flashes the corresponding Statbar when its value changes
+///
+/// Changes are measured against the target of a `StatbarTween`, if the Statbar has one.
+/// The flash is drawn over the full and empty parts of the bar, not its border.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarFlash<T>
+where
+    T: 'static,
+{
+    /// color of the flash
+    pub color: Color,
+    /// false => the bar's colors are mixed towards `color`,
+    /// true => `color` is added to the bar's colors, brightening them
+    pub additive: bool,
+    /// seconds the flash takes to fade out
+    pub duration: f32,
+    pub trigger: StatbarTrigger,
+    /// smallest change in value that triggers a flash
+    pub min_delta: f32,
+    /// seconds left before the flash has faded out
+    #[reflect(ignore)]
+    remaining: f32,
+    #[reflect(ignore)]
+    last_value: Option<f32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarFlash<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Color::WHITE)
+    }
+}
+
+impl<T> StatbarFlash<T>
+where
+    T: 'static,
+{
+    /// A short flash towards `color` whenever the value drops
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            additive: false,
+            duration: 0.2,
+            trigger: StatbarTrigger::Drop,
+            min_delta: 0.,
+            remaining: 0.,
+            last_value: None,
+            phantom: Default::default(),
+        }
+    }
+
+    /// A short additive white flash whenever the value drops
+    pub fn additive() -> Self {
+        Self {
+            additive: true,
+            ..Self::new(Color::WHITE)
+        }
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Flash on the given changes of at least `min_delta`
+    pub fn on(mut self, trigger: StatbarTrigger, min_delta: f32) -> Self {
+        self.trigger = trigger;
+        self.min_delta = min_delta;
+        self
+    }
+
+    /// How strong the flash is now, from 0.0 to 1.0
+    pub fn intensity(&self) -> f32 {
+        if 0. < self.duration {
+            (self.remaining / self.duration).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+
+    /// `color` with the flash applied
+    pub(crate) fn apply(&self, color: LinearRgba) -> LinearRgba {
+        let intensity = self.intensity();
+        if intensity <= 0. {
+            return color;
+        }
+        let flash = self.color.to_linear();
+        if self.additive {
+            LinearRgba {
+                alpha: color.alpha,
+                ..color + flash * intensity
+            }
+        } else {
+            color.mix(&flash, intensity)
+        }
+    }
+
+    fn update(&mut self, value: f32, delta_seconds: f32) {
+        self.remaining = (self.remaining - delta_seconds).max(0.);
+        if let Some(last_value) = self.last_value {
+            if self.trigger.is_triggered(last_value, value, self.min_delta) {
+                self.remaining = self.duration;
+            }
+        }
+        self.last_value = Some(value);
+    }
+}
+
+/// Shakes the corresponding Statbar when its value changes
+///
+/// Changes are measured against the target of a `StatbarTween`, if the Statbar has one.
+/// The shake is added to the Statbar's displacement when drawn, and decays over its duration.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarShake<T>
+where
+    T: 'static,
+{
+    /// largest distance the bar is moved from its position
+    pub amplitude: f32,
+    /// seconds the shake takes to die down
+    pub duration: f32,
+    /// shakes per second
+    pub frequency: f32,
+    pub trigger: StatbarTrigger,
+    /// smallest change in value that triggers a shake
+    pub min_delta: f32,
+    /// seconds left before the shake has died down
+    #[reflect(ignore)]
+    remaining: f32,
+    /// current displacement of the bar
+    #[reflect(ignore)]
+    pub(crate) offset: Vec2,
+    #[reflect(ignore)]
+    last_value: Option<f32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarShake<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(3.)
+    }
+}
+
+impl<T> StatbarShake<T>
+where
+    T: 'static,
+{
+    /// A short shake whenever the value drops
+    pub fn new(amplitude: f32) -> Self {
+        Self {
+            amplitude,
+            duration: 0.3,
+            frequency: 25.,
+            trigger: StatbarTrigger::Drop,
+            min_delta: 0.,
+            remaining: 0.,
+            offset: Vec2::ZERO,
+            last_value: None,
+            phantom: Default::default(),
+        }
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Shake on the given changes of at least `min_delta`
+    pub fn on(mut self, trigger: StatbarTrigger, min_delta: f32) -> Self {
+        self.trigger = trigger;
+        self.min_delta = min_delta;
+        self
+    }
+
+    fn update(&mut self, value: f32, delta_seconds: f32) {
+        self.remaining = (self.remaining - delta_seconds).max(0.);
+        if let Some(last_value) = self.last_value {
+            if self.trigger.is_triggered(last_value, value, self.min_delta) {
+                self.remaining = self.duration;
+            }
+        }
+        self.last_value = Some(value);
+        self.offset = if 0. < self.remaining && 0. < self.duration {
+            let decay = self.remaining / self.duration;
+            // two out of step oscillations, so the bar doesn't just move along a line
+            let phase = std::f32::consts::TAU * self.frequency * (self.duration - self.remaining);
+            self.amplitude * decay * Vec2::new(phase.sin(), (1.3 * phase + 1.).sin())
+        } else {
+            Vec2::ZERO
+        };
+    }
+}
+
+/// The shape of the repeating wave driving a `StatbarAlert`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarWaveform {
+    /// smooth rise and fall
+    #[default]
+    Sine,
+    /// switches on and off, half the time each
+    Square,
+    /// rises and falls at a constant rate
+    Triangle,
+}
+
+impl StatbarWaveform {
+    /// The height of the wave, from 0.0 to 1.0, at `phase` between 0.0 and 1.0
+    pub fn sample(self, phase: f32) -> f32 {
+        let phase = phase.rem_euclid(1.);
+        match self {
+            StatbarWaveform::Sine => 0.5 - 0.5 * (std::f32::consts::TAU * phase).cos(),
+            StatbarWaveform::Square => {
+                if phase < 0.5 {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            StatbarWaveform::Triangle => 1. - (2. * phase - 1.).abs(),
+        }
+    }
+}
+
+/// What a `StatbarAlert` does to its Statbar
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+pub enum StatbarAlertEffect {
+    /// fades the whole bar, including its border, down to `min_alpha` and back
+    Blink { min_alpha: f32 },
+    /// grows the thickness of the bar by up to the fraction `thickness`
+    /// and brightens its colors by up to the fraction `brightness`.
+    /// The thickness of radial bars doesn't change.
+    Pulse { thickness: f32, brightness: f32 },
+}
+
+/// Makes the corresponding Statbar blink or pulse while its value is below a threshold
+///
+/// Could be used to draw the player's eye to a health bar when they are about to die.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarAlert<T>
+where
+    T: 'static,
+{
+    /// the alert is active while `statbar.value < threshold`
+    pub threshold: f32,
+    pub effect: StatbarAlertEffect,
+    /// blinks or pulses per second
+    pub frequency: f32,
+    pub waveform: StatbarWaveform,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarAlert<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::blink(0.25)
+    }
+}
+
+impl<T> StatbarAlert<T>
+where
+    T: 'static,
+{
+    pub fn new(threshold: f32, effect: StatbarAlertEffect) -> Self {
+        Self {
+            threshold,
+            effect,
+            frequency: 2.,
+            waveform: StatbarWaveform::default(),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Blink the bar on and off twice a second while the value is below `threshold`
+    pub fn blink(threshold: f32) -> Self {
+        Self {
+            waveform: StatbarWaveform::Square,
+            ..Self::new(threshold, StatbarAlertEffect::Blink { min_alpha: 0. })
+        }
+    }
+
+    /// Pulse the bar twice a second while the value is below `threshold`
+    pub fn pulse(threshold: f32) -> Self {
+        Self::new(
+            threshold,
+            StatbarAlertEffect::Pulse {
+                thickness: 0.25,
+                brightness: 0.5,
+            },
+        )
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_waveform(mut self, waveform: StatbarWaveform) -> Self {
+        self.waveform = waveform;
+        self
+    }
+
+    /// The height of the alert's wave after `seconds`
+    pub(crate) fn wave(&self, seconds: f32) -> f32 {
+        self.waveform.sample(self.frequency * seconds)
+    }
+}
+
+/// Hides the corresponding Statbar automatically, fading it in and out
+///
+/// Could be used to only show the health bars of damaged units.
+/// The bar is shown again whenever its value changes, unless one of the other rules hides it.
+/// A bipolar bar is full at -1.0 and 1.0 and empty at 0.0.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    /// hide the bar while `statbar.value >= 1.0`
+    pub when_full: bool,
+    /// hide the bar while `statbar.value <= 0.0`
+    pub when_empty: bool,
+    /// if set, hide the bar this many seconds after its value last changed
+    pub after: Option<f32>,
+    /// seconds taken to fade in
+    pub fade_in: f32,
+    /// seconds taken to fade out
+    pub fade_out: f32,
+    /// multiplies the alpha of the bar, 0.0 => hidden
+    #[reflect(ignore)]
+    pub(crate) opacity: f32,
+    /// seconds since the value last changed
+    #[reflect(ignore)]
+    since_change: f32,
+    #[reflect(ignore)]
+    last_value: Option<f32>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            when_full: false,
+            when_empty: false,
+            after: None,
+            fade_in: 0.1,
+            fade_out: 0.5,
+            opacity: 1.,
+            since_change: 0.,
+            last_value: None,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarAutoHide<T>
+where
+    T: 'static,
+{
+    /// Hide the bar while it is full
+    pub fn when_full() -> Self {
+        Self {
+            when_full: true,
+            ..Default::default()
+        }
+    }
+
+    /// Hide the bar while it is empty
+    pub fn when_empty() -> Self {
+        Self {
+            when_empty: true,
+            ..Default::default()
+        }
+    }
+
+    /// Hide the bar `seconds` after its value last changed
+    pub fn after(seconds: f32) -> Self {
+        Self {
+            after: Some(seconds),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_fades(mut self, fade_in: f32, fade_out: f32) -> Self {
+        self.fade_in = fade_in;
+        self.fade_out = fade_out;
+        self
+    }
+
+    /// Whether the rules currently hide a bar with the given value
+    pub fn hides(&self, value: f32) -> bool {
+        (self.when_full && 1. <= value)
+            || (self.when_empty && value <= 0.)
+            || self.after.is_some_and(|after| after <= self.since_change)
+    }
+
+    fn update(&mut self, value: f32, delta_seconds: f32) {
+        if self
+            .last_value
+            .is_some_and(|last_value| last_value != value)
+        {
+            self.since_change = 0.;
+        } else {
+            self.since_change += delta_seconds;
+        }
+        if self.last_value.is_none() {
+            // start out already shown or hidden, without fading
+            self.opacity = if self.hides(value) { 0. } else { 1. };
+        }
+        self.last_value = Some(value);
+        let (target, fade) = if self.hides(value) {
+            (0., self.fade_out)
+        } else {
+            (1., self.fade_in)
+        };
+        let step = if 0. < fade { delta_seconds / fade } else { 1. };
+        self.opacity += (target - self.opacity).clamp(-step, step);
+    }
+}
+
+/// Only draws the corresponding Statbar while its entity, or the entity it observes,
+/// is hovered by the cursor or selected
+///
+/// The observed entity is the one linked by `StatbarObserveParent` or `StatbarObserveEntity`,
+/// if there is one. It is hovered while the cursor is over its bounds: a rectangle of
+/// `hover_size` centered on it if set, otherwise its `Sprite`, otherwise the bar itself.
+/// It is selected while it has any of the marker components registered with
+/// `add_statbar_selection_marker`.
+///
+/// Hovering is only tracked for the statbar types registered with `add_statbar_focus`
+/// or `add_statbar_selection_marker`, for other types a warning is logged and the bar is never drawn.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarShowOnFocus<T>
+where
+    T: 'static,
+{
+    /// show the bar while the cursor is over the observed entity
+    pub hover: bool,
+    /// show the bar while the observed entity is selected
+    pub selected: bool,
+    /// size of the hovered area around the observed entity
+    pub hover_size: Option<Vec2>,
+    #[reflect(ignore)]
+    pub(crate) is_hovered: bool,
+    #[reflect(ignore)]
+    pub(crate) is_selected: bool,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarShowOnFocus<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            hover: true,
+            selected: true,
+            hover_size: None,
+            is_hovered: false,
+            is_selected: false,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarShowOnFocus<T>
+where
+    T: 'static,
+{
+    /// Show the bar only while the observed entity is hovered
+    pub fn hover() -> Self {
+        Self {
+            selected: false,
+            ..Default::default()
+        }
+    }
+
+    /// Show the bar only while the observed entity is selected
+    pub fn selected() -> Self {
+        Self {
+            hover: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_hover_size(mut self, size: Vec2) -> Self {
+        self.hover_size = Some(size);
+        self
+    }
+
+    /// Whether the bar is drawn
+    pub fn is_shown(&self) -> bool {
+        (self.hover && self.is_hovered) || (self.selected && self.is_selected)
+    }
+}
+
+/// Leaves the lost part of the corresponding Statbar visible as a trail when its value drops,
+/// which then shrinks back to the new value
+///
+/// Makes big hits easier to read. Further drops while the trail is shown restart the delay.
+/// Not drawn for bipolar, pip or icon bars.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarTrail<T>
+where
+    T: 'static,
+{
+    /// color of the trail
+    pub color: Color,
+    /// seconds after a drop before the trail starts to shrink
+    pub delay: f32,
+    /// how fast the trail shrinks, in values per second
+    pub speed: f32,
+    /// value the trail currently reaches to
+    #[reflect(ignore)]
+    pub(crate) value: f32,
+    /// seconds left before the trail starts to shrink
+    #[reflect(ignore)]
+    timer: f32,
+    /// value of the bar when last updated
+    #[reflect(ignore)]
+    last_value: f32,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarTrail<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(Color::WHITE)
+    }
+}
+
+impl<T> StatbarTrail<T>
+where
+    T: 'static,
+{
+    /// A trail that waits half a second then shrinks by one whole bar a second
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            delay: 0.5,
+            speed: 1.,
+            value: f32::NEG_INFINITY,
+            timer: 0.,
+            last_value: f32::NEG_INFINITY,
+            phantom: Default::default(),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Follows the bar's value, lagging behind when it drops
+    fn update(&mut self, value: f32, delta_seconds: f32) {
+        let dropped = value < self.last_value;
+        self.last_value = value;
+        if self.value <= value {
+            self.value = value;
+            self.timer = 0.;
+            return;
+        }
+        if dropped {
+            self.timer = self.delay;
+        }
+        if 0. < self.timer {
+            self.timer -= delta_seconds;
+        } else {
+            self.value = (self.value - self.speed * delta_seconds).max(value);
+        }
+    }
+}
+
+/// Draws the corresponding Statbar with images instead of flat colors.
+///
+/// The bar's colors tint the images, set them to `Color::WHITE` to draw the images unchanged.
+/// The fill image is cropped to the value of the bar instead of being squashed.
+/// Images are drawn as they are, a vertical bar needs vertically orientated art.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarTexture<T>
+where
+    T: 'static,
+{
+    /// image for the full part of the bar
+    pub fill: Option<Handle<Image>>,
+    /// image for the empty part of the bar
+    pub empty: Option<Handle<Image>>,
+    /// image for the border, only drawn if the bar has a `StatbarBorder`
+    pub border: Option<Handle<Image>>,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarTexture<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            fill: None,
+            empty: None,
+            border: None,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarTexture<T>
+where
+    T: 'static,
+{
+    pub fn new(fill: Handle<Image>, empty: Handle<Image>) -> Self {
+        Self {
+            fill: Some(fill),
+            empty: Some(empty),
+            ..Default::default()
+        }
+    }
+
+    /// Use `image` for the border
+    pub fn with_border(mut self, image: Handle<Image>) -> Self {
+        self.border = Some(image);
+        self
+    }
+}
+
+/// The color space colors are interpolated in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarColorSpace {
+    /// gamma encoded sRGB
+    #[default]
+    Srgb,
+    /// linear sRGB
+    LinearRgb,
+    /// perceptually uniform, gives even midpoints without muddy browns or grays
+    Oklab,
+    /// cylindrical Oklab, hue takes the shortest path around the color wheel
+    Oklch,
+    /// hue takes the shortest path around the color wheel
+    Hsl,
+}
+
+impl StatbarColorSpace {
+    /// Mix `start` and `end` in this color space,
+    /// `factor` == 0. => `start`, `factor` == 1. => `end`
+    pub fn mix(self, start: Color, end: Color, factor: f32) -> Color {
+        match self {
+            StatbarColorSpace::Srgb => Srgba::from(start).mix(&end.into(), factor).into(),
+            StatbarColorSpace::LinearRgb => start.to_linear().mix(&end.to_linear(), factor).into(),
+            StatbarColorSpace::Oklab => Oklaba::from(start).mix(&end.into(), factor).into(),
+            StatbarColorSpace::Oklch => Oklcha::from(start).mix(&end.into(), factor).into(),
+            StatbarColorSpace::Hsl => Hsla::from(start).mix(&end.into(), factor).into(),
+        }
+    }
+}
+
+/// Linearly interpolate the value of the bar color
+/// between `min` and `max` using the value of the Statbar
+/// * statbar.value == 0. => statbar.display_color == min
+/// * statbar.value == 1. => statbar.display_color == max
+///
+/// For a bipolar bar `min` is the color at -1.0 and `max` the color at 1.0.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarColorLerp<T>
+where
+    T: 'static,
+{
+    /// bar color when value is 0.0
+    pub min: Color,
+    /// bar color when value is 1.0
+    pub max: Color,
+    /// color space the colors are interpolated in
+    pub space: StatbarColorSpace,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorLerp<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            min: Color::from(bevy::color::palettes::css::RED),
+            max: Color::from(bevy::color::palettes::css::GREEN),
+            space: StatbarColorSpace::default(),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarColorLerp<T>
+where
+    T: 'static,
+{
+    pub fn new(min: Color, max: Color) -> Self {
+        Self {
+            min,
+            max,
+            space: StatbarColorSpace::default(),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Interpolate in the given color space
+    pub fn in_space(mut self, space: StatbarColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+}
+
+/// Change the statbar color depending on the value of the statbar's subject
+///
+/// Could be used for a health bar that
+/// turns to red when the character has less than 25% health remaining.
+///
+/// For more than two colors use `StatbarColorBands`.
+#[derive(Clone, Copy, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarColorSwitch<T>
+where
+    T: 'static,
+{
+    /// * `statbar.value <= pivot`
+    ///   => sets bar's color to `low`
+    /// * `pivot < statbar.value`
+    ///   => sets bar's color to `high`
+    pub pivot: f32,
+    /// statbar color when the statbar's value is less than or equal to pivot
+    pub low: Color,
+    /// statbar color when the statbar's value is greater than pivot
+    pub high: Color,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorSwitch<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self {
+            pivot: 0.25,
+            low: Color::from(bevy::color::palettes::css::RED),
+            high: Color::from(bevy::color::palettes::css::GREEN),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T> StatbarColorSwitch<T>
+where
+    T: 'static,
+{
+    pub fn new(pivot: f32, low: Color, high: Color) -> Self {
+        Self {
+            pivot,
+            low,
+            high,
+            phantom: Default::default(),
+        }
+    }
+}
+
+/// Change the statbar color when the value of the statbar's subject crosses any of a list of thresholds
+///
+/// Could be used for a shield bar with separate critical, low, normal and overcharged colors.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarColorBands<T>
+where
+    T: 'static,
+{
+    /// statbar color when the statbar's value is less than or equal to every threshold
+    pub base: Color,
+    /// thresholds in increasing order, each with the statbar color used
+    /// when the statbar's value is greater than that threshold
+    pub thresholds: Vec<(f32, Color)>,
+    /// how far the statbar's value has to move past a threshold before the color changes,
+    /// stops the color flickering when the value hovers around a threshold
+    pub hysteresis: f32,
+    /// index of the current band, 0 is the base color
+    #[reflect(ignore)]
+    band: usize,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorBands<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new(
+            Color::from(bevy::color::palettes::css::RED),
+            [
+                (0.25, Color::from(bevy::color::palettes::css::ORANGE)),
+                (0.5, Color::from(bevy::color::palettes::css::GREEN)),
+            ],
+        )
+    }
+}
+
+impl<T> StatbarColorBands<T>
+where
+    T: 'static,
+{
+    /// Bands without hysteresis, the thresholds are sorted in increasing order
+    pub fn new(base: Color, thresholds: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut thresholds: Vec<_> = thresholds.into_iter().collect();
+        thresholds.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self {
+            base,
+            thresholds,
+            hysteresis: 0.,
+            band: 0,
+            phantom: Default::default(),
+        }
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Moves to the band containing `value` and returns its color
+    fn update(&mut self, value: f32) -> Color {
+        let band_at = |offset: f32| {
+            self.thresholds
+                .iter()
+                .take_while(|(threshold, _)| threshold + offset < value)
+                .count()
+        };
+        let current = self.band.min(self.thresholds.len());
+        // only leave the current band once the value is more than `hysteresis` past its edge
+        self.band = band_at(self.hysteresis)
+            .max(current)
+            .min(band_at(-self.hysteresis));
+        match self.band {
+            0 => self.base,
+            band => self.thresholds[band - 1].1,
+        }
+    }
+}
+
+/// How a `StatbarColorGradient` colors its Statbar
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum StatbarGradientMode {
+    /// the whole bar is colored with the gradient sampled at the value of the Statbar
+    #[default]
+    Value,
+    /// the gradient is painted along the length of the bar, from its empty end to its full end.
+    /// Ignored for bars with a `StatbarTexture` fill image.
+    Length,
+}
+
+/// Color the statbar using a gradient with any number of color stops
+///
+/// Could be used for a health bar that goes from green through yellow and orange to red.
+/// For a bipolar bar the gradient is sampled from 0.0 at -1.0 to 1.0 at 1.0.
+#[derive(Clone, Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct StatbarColorGradient<T>
+where
+    T: 'static,
+{
+    /// positions between 0.0 and 1.0 and their colors, in order of increasing position
+    pub stops: Vec<(f32, Color)>,
+    pub mode: StatbarGradientMode,
+    /// color space neighbouring stops are interpolated in
+    pub space: StatbarColorSpace,
+    #[reflect(ignore)]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for StatbarColorGradient<T>
+where
+    T: 'static,
+{
+    fn default() -> Self {
+        Self::new([
+            (0.0, Color::from(bevy::color::palettes::css::RED)),
+            (0.25, Color::from(bevy::color::palettes::css::ORANGE)),
+            (0.5, Color::from(bevy::color::palettes::css::YELLOW)),
+            (1.0, Color::from(bevy::color::palettes::css::GREEN)),
+        ])
+    }
+}
+
+impl<T> StatbarColorGradient<T>
+where
+    T: 'static,
+{
+    /// A gradient sampled by value, the stops are sorted by position
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut stops: Vec<_> = stops.into_iter().collect();
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self {
+            stops,
+            mode: StatbarGradientMode::Value,
+            space: StatbarColorSpace::default(),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Paint the gradient along the length of the bar instead
+    pub fn along_length(mut self) -> Self {
+        self.mode = StatbarGradientMode::Length;
+        self
+    }
+
+    /// Interpolate between the stops in the given color space
+    pub fn in_space(mut self, space: StatbarColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// The color of the gradient at `position`.
+    /// Positions before the first stop or after the last take the color of that stop.
+    pub fn sample(&self, position: f32) -> Color {
+        let Some(&(first_position, first_color)) = self.stops.first() else {
+            return Color::WHITE;
+        };
+        if position <= first_position {
+            return first_color;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, start_color), (end, end_color)) = (pair[0], pair[1]);
+            if position <= end {
+                let span = end - start;
+                let t = if 0. < span {
+                    (position - start) / span
+                } else {
+                    1.
+                };
+                return self.space.mix(start_color, end_color, t);
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+/// Sets the display color of a statbar without marking it as changed,
+/// as the display color is rebuilt every frame
+fn set_display_color<T: TypePath>(bar: &mut Mut<Statbar<T>>, color: Color) {
+    if bar.display_color != color {
+        bar.bypass_change_detection().display_color = color;
+    }
+}
+
+fn reset_stat_bar_colors<T>(mut statbar_query: Query<&mut Statbar<T>>)
+where
+    T: 'static + TypePath,
+{
+    statbar_query.iter_mut().for_each(|mut bar| {
+        let color = bar.color;
+        set_display_color(&mut bar, color);
+    });
+}
+
+fn switch_stat_bar_colors<T>(
+    mut color_switch_query: Query<(&mut Statbar<T>, &StatbarColorSwitch<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    color_switch_query
+        .iter_mut()
+        .for_each(|(mut bar, switcher)| {
+            let color = if bar.value <= switcher.pivot {
+                switcher.low
+            } else {
+                switcher.high
+            };
+            set_display_color(&mut bar, color);
+        });
+}
+
+fn band_stat_bar_colors<T>(
+    mut color_bands_query: Query<(&mut Statbar<T>, &mut StatbarColorBands<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    color_bands_query
+        .iter_mut()
+        .for_each(|(mut bar, mut bands)| {
+            let color = bands.bypass_change_detection().update(bar.value);
+            set_display_color(&mut bar, color);
+        });
+}
+
+fn lerp_stat_bar_colors<T>(mut color_lerp_query: Query<(&mut Statbar<T>, &StatbarColorLerp<T>)>)
+where
+    T: 'static + TypePath,
+{
+    color_lerp_query.iter_mut().for_each(|(mut bar, lerper)| {
+        let color = lerper
+            .space
+            .mix(lerper.min, lerper.max, bar.unipolar_value());
+        set_display_color(&mut bar, color);
+    });
+}
+
+fn gradient_stat_bar_colors<T>(
+    mut color_gradient_query: Query<(&mut Statbar<T>, &StatbarColorGradient<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    color_gradient_query
+        .iter_mut()
+        .filter(|(_, gradient)| gradient.mode == StatbarGradientMode::Value)
+        .for_each(|(mut bar, gradient)| {
+            let color = gradient.sample(bar.unipolar_value());
+            set_display_color(&mut bar, color);
+        });
+}
+
+/// The time passed since the last frame, or no time at all in apps without a `TimePlugin`
+fn delta_seconds<C: Default + Send + Sync + 'static>(time: &Option<Res<Time<C>>>) -> f32 {
+    time.as_ref().map_or(0., |time| time.delta_seconds())
+}
+
+fn tween_statbar_values<T>(
+    virtual_time: Option<Res<Time<Virtual>>>,
+    real_time: Option<Res<Time<Real>>>,
+    mut tween_query: Query<(&mut Statbar<T>, &mut StatbarTween<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    tween_query.iter_mut().for_each(|(mut bar, mut tween)| {
+        let delta_seconds = if tween.real_time {
+            delta_seconds(&real_time)
+        } else {
+            delta_seconds(&virtual_time)
+        };
+        let value = tween.update(bar.value, delta_seconds);
+        if bar.value != value {
+            bar.value = value;
+        }
+    });
+}
+
+fn update_statbar_lengths<T>(
+    time: Option<Res<Time>>,
+    mut length_query: Query<(&mut Statbar<T>, &mut StatbarLengthFromMax<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    let delta_seconds = delta_seconds(&time);
+    length_query.iter_mut().for_each(|(mut bar, mut length)| {
+        let length = length.update(bar.max, delta_seconds);
+        if bar.length != length {
+            bar.length = length;
+        }
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_stat_bar_impacts<T>(
+    time: Option<Res<Time>>,
+    mut impact_query: Query<(
+        &Statbar<T>,
+        Option<&StatbarTween<T>>,
+        Option<&mut StatbarFlash<T>>,
+        Option<&mut StatbarShake<T>>,
+    )>,
+) where
+    T: 'static + TypePath,
+{
+    let delta_seconds = delta_seconds(&time);
+    impact_query
+        .iter_mut()
+        .for_each(|(bar, tween, flash, shake)| {
+            let value = tween.map(|tween| tween.target()).unwrap_or(bar.value);
+            if let Some(mut flash) = flash {
+                flash.update(value, delta_seconds);
+            }
+            if let Some(mut shake) = shake {
+                shake.update(value, delta_seconds);
+            }
+        });
+}
+
+fn update_stat_bar_auto_hide<T>(
+    time: Option<Res<Time>>,
+    mut auto_hide_query: Query<(&Statbar<T>, &mut StatbarAutoHide<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    let delta_seconds = delta_seconds(&time);
+    auto_hide_query.iter_mut().for_each(|(bar, mut auto_hide)| {
+        // a bipolar bar is as full as it is far from its center
+        let value = if bar.bipolar.is_some() {
+            bar.value.abs()
+        } else {
+            bar.value
+        };
+        auto_hide.update(value, delta_seconds);
+    });
+}
+
+/// The entity a statbar observes, or the statbar's own entity
+fn observed_entity(
+    entity: Entity,
+    observe_entity: Option<&StatbarObserveEntity>,
+    observe_parent: Option<(&StatbarObserveParent, &Parent)>,
+) -> Entity {
+    match (observe_parent, observe_entity) {
+        (Some((_, parent)), _) => parent.get(),
+        (None, Some(&StatbarObserveEntity(target))) => target,
+        (None, None) => entity,
+    }
+}
+
+/// The area covered by a straight statbar, or a radial statbar's ring
+fn bar_bounds<T: TypePath>(bar: &Statbar<T>, transform: &GlobalTransform) -> Rect {
+    let center = transform.translation().truncate() + bar.displacement;
+    let size = match bar.radial {
+        Some(radial) => Vec2::splat(2. * radial.outer_radius),
+        None if bar.vertical => Vec2::new(bar.thickness, bar.length),
+        None => Vec2::new(bar.length, bar.thickness),
+    };
+    Rect::from_center_size(center, size)
+}
+
+/// The position of the cursor in world space, shared by the hover checks of every statbar type
+#[derive(Resource, Default)]
+struct StatbarCursor(Option<Vec2>);
+
+fn update_statbar_cursor(
+    window_query: Query<&Window, With<bevy::window::PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut cursor: ResMut<StatbarCursor>,
+) {
+    cursor.0 = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| {
+            camera_query
+                .iter()
+                .filter(|(camera, _)| camera.is_active)
+                .find_map(|(camera, camera_transform)| {
+                    camera.viewport_to_world_2d(camera_transform, cursor)
+                })
+        });
+}
+
+/// Inserted once the hover and selection systems of `StatbarShowOnFocus<T>` are added
+#[derive(Resource)]
+struct StatbarFocusAdded<T: 'static>(PhantomData<fn() -> T>);
+
+/// Warns once if a `StatbarShowOnFocus<T>` is added while focus isn't tracked for `T`,
+/// as those bars would never be drawn
+fn warn_statbar_focus_not_added<T>(
+    mut warned: Local<bool>,
+    focus_query: Query<(), Added<StatbarShowOnFocus<T>>>,
+) where
+    T: 'static + TypePath,
+{
+    if !*warned && !focus_query.is_empty() {
+        *warned = true;
+        warn!(
+            "bevy_stat_bars: `StatbarShowOnFocus<{}>` is never shown without `add_statbar_focus::<{}>` or `add_statbar_selection_marker`",
+            T::short_type_path(),
+            T::short_type_path()
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_statbar_hover<T>(
+    cursor: Res<StatbarCursor>,
+    images: Res<Assets<Image>>,
+    mut focus_query: Query<(
+        Entity,
+        &Statbar<T>,
+        &mut StatbarShowOnFocus<T>,
+        &GlobalTransform,
+        Option<&StatbarObserveEntity>,
+        Option<(&StatbarObserveParent, &Parent)>,
+    )>,
+    target_query: Query<(&GlobalTransform, Option<&Sprite>, Option<&Handle<Image>>)>,
+) where
+    T: 'static + TypePath,
+{
+    let cursor = cursor.0;
+    let sprite_size = |sprite: &Sprite, image: Option<&Handle<Image>>| {
+        sprite.custom_size.or_else(|| {
+            let image = images.get(image?)?;
+            Some(sprite.rect.map_or(image.size_f32(), |rect| rect.size()))
+        })
+    };
+    focus_query.iter_mut().for_each(
+        |(entity, bar, mut focus, transform, observe_entity, observe_parent)| {
+            focus.is_selected = false;
+            let Some(cursor) = cursor.filter(|_| focus.hover) else {
+                focus.is_hovered = false;
+                return;
+            };
+            let target = observed_entity(entity, observe_entity, observe_parent);
+            let bounds = match (focus.hover_size, target_query.get(target).ok()) {
+                (Some(size), Some((target_transform, ..))) => {
+                    Rect::from_center_size(target_transform.translation().truncate(), size)
+                }
+                (None, Some((target_transform, Some(sprite), image))) => {
+                    match sprite_size(sprite, image) {
+                        Some(size) => {
+                            let size = size * target_transform.compute_transform().scale.truncate();
+                            let center = target_transform.translation().truncate()
+                                - sprite.anchor.as_vec() * size;
+                            Rect::from_center_size(center, size)
+                        }
+                        None => bar_bounds(bar, transform),
+                    }
+                }
+                _ => bar_bounds(bar, transform),
+            };
+            focus.is_hovered = bounds.contains(cursor);
+        },
+    );
+}
+
+#[allow(clippy::type_complexity)]
+fn update_statbar_selection<T, M>(
+    mut focus_query: Query<(
+        Entity,
+        &mut StatbarShowOnFocus<T>,
+        Option<&StatbarObserveEntity>,
+        Option<(&StatbarObserveParent, &Parent)>,
+    )>,
+    marker_query: Query<(), With<M>>,
+) where
+    T: 'static + TypePath,
+    M: Component,
+{
+    focus_query
+        .iter_mut()
+        .for_each(|(entity, mut focus, observe_entity, observe_parent)| {
+            let target = observed_entity(entity, observe_entity, observe_parent);
+            if marker_query.contains(target) {
+                focus.is_selected = true;
+            }
+        });
+}
+
+fn update_stat_bar_trails<T>(
+    time: Option<Res<Time>>,
+    mut trail_query: Query<(&Statbar<T>, &mut StatbarTrail<T>)>,
+) where
+    T: 'static + TypePath,
+{
+    let delta_seconds = delta_seconds(&time);
+    trail_query.iter_mut().for_each(|(bar, mut trail)| {
+        trail.update(bar.value, delta_seconds);
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_statbar_values<T>(
+    mut statbar_query: Query<
         (&mut Statbar<T>, &T),
         (
             Changed<T>,
@@ -298,7 +2448,7 @@ fn update_statbar_values<T>(
     T: Component + StatbarObservable + TypePath,
 {
     statbar_query.iter_mut().for_each(|(mut statbar, value)| {
-        statbar.value = value.get_statbar_value();
+        statbar.observe(value);
     });
 }
 
@@ -314,7 +2464,7 @@ fn update_statbar_values_from_parents<T>(
 {
     statbar_query.iter_mut().for_each(|(mut statbar, parent)| {
         if let Ok(value) = parent_value_query.get(parent.get()) {
-            statbar.value = value.get_statbar_value();
+            statbar.observe(value);
         }
     });
 }
@@ -332,18 +2482,61 @@ fn update_statbar_values_from_other<T>(
         .iter_mut()
         .for_each(|(mut statbar, &StatbarObserveEntity(target))| {
             if let Ok(value) = other_value_query.get(target) {
-                statbar.value = value.get_statbar_value();
+                statbar.observe(value);
             }
         });
 }
 
+/// Sets `segment` of each `StatbarStack<T>` to the current amount of the observed `S` component,
+/// measured against the max of the `Statbar<T>` so all the segments share the bar's scale
+#[allow(clippy::type_complexity)]
+fn update_statbar_stack_values<T, S>(
+    segment: usize,
+) -> impl FnMut(
+    Query<(
+        Entity,
+        &Statbar<T>,
+        &mut StatbarStack<T>,
+        Option<&StatbarObserveEntity>,
+        Option<(&StatbarObserveParent, &Parent)>,
+    )>,
+    Query<&S>,
+)
+where
+    T: 'static + TypePath,
+    S: Component + StatbarObservable,
+{
+    move |mut stack_query, value_query| {
+        stack_query.iter_mut().for_each(
+            |(entity, bar, mut stack, observe_entity, observe_parent)| {
+                let target = observed_entity(entity, observe_entity, observe_parent);
+                let Ok(observed) = value_query.get(target) else {
+                    return;
+                };
+                let value = if 0. < bar.max {
+                    observed.get_statbar_current() / bar.max
+                } else {
+                    0.
+                };
+                if stack
+                    .segments
+                    .get(segment)
+                    .is_some_and(|segment| segment.value != value)
+                {
+                    stack.segments[segment].value = value;
+                }
+            },
+        );
+    }
+}
+
 fn update_statbar_from_resource<T>(resource: Res<T>, mut statbar_query: Query<&mut Statbar<T>>)
 where
     T: StatbarObservable + 'static + Send + Sync + TypePath + Resource,
 {
     if resource.is_changed() {
         statbar_query.iter_mut().for_each(|mut statbar| {
-            statbar.value = resource.get_statbar_value();
+            statbar.observe(&*resource);
         });
     }
 }
@@ -351,10 +2544,46 @@ where
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 pub enum StatbarSystem {
     UpdateValues,
+    /// eases the bars with a `StatbarTween` towards their updated values,
+    /// and sets the lengths of the bars with a `StatbarLengthFromMax`
+    /// and the observed segments of the `StatbarStack`s from the updated max values
+    TweenValues,
     UpdateColors,
+    /// animations following the updated values and colors
+    UpdateEffects,
+    /// moves the `StatbarLabel` texts onto their bars, after the transforms are propagated.
+    /// Only used with the `labels` feature.
+    UpdateLabels,
     ExtractSprites,
 }
 
+/// The stages of `StatbarSystem::UpdateColors`, run in this order.
+///
+/// `Statbar::color` is the base color of the bar and is never changed by these stages.
+/// Every frame the `Base` stage resets `Statbar::display_color`, the color the bar is drawn
+/// with, to the base color. Each following stage replaces the display color left by the
+/// stages before it, so if a bar has more than one color component the component from the
+/// latest stage takes precedence.
+///
+/// To add your own color modifier put its system in `StatbarSystem::UpdateColors` and order it
+/// relative to these stages, for example `.after(StatbarColorSystem::Lerp)` to override lerped
+/// colors but be overridden by switches and bands. A modifier can read the display color left
+/// by the earlier stages and blend with it instead of replacing it. As the display color is
+/// rebuilt from the base color every frame, the blend is applied once rather than compounding.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum StatbarColorSystem {
+    /// resets the display colors to the base colors
+    Base,
+    /// `StatbarColorLerp`
+    Lerp,
+    /// `StatbarColorGradient` in `StatbarGradientMode::Value`
+    Gradient,
+    /// `StatbarColorSwitch`
+    Switch,
+    /// `StatbarColorBands`
+    Bands,
+}
+
 pub trait RegisterStatbarSubject {
     fn add_statbar_component_observer<T: StatbarObservable + Component + TypePath>(
         &mut self,
@@ -365,41 +2594,130 @@ pub trait RegisterStatbarSubject {
         &mut self,
     ) -> &mut Self;
     fn add_standalone_statbar<T: TypePath + 'static>(&mut self) -> &mut Self;
+    /// Observe the `S` component for the `StatbarStack<T>`s,
+    /// setting the stack's `segment` to the current amount of `S` over the max of the `Statbar<T>`.
+    /// Follows `StatbarObserveEntity` and `StatbarObserveParent` like the Statbar does.
+    fn add_statbar_stack_observer<T: TypePath + 'static, S: StatbarObservable + Component>(
+        &mut self,
+        segment: usize,
+    ) -> &mut Self;
+    /// Only draw the `Statbar<T>`s while `condition` holds,
+    /// e.g. `show_statbars_while::<HitPoints, _>(input_pressed(KeyCode::AltLeft))`.
+    /// Inserts a `StatbarTypeVisibility<T>` in `StatbarVisibilityMode::WhileCondition` mode.
+    fn show_statbars_while<T: TypePath + 'static, M>(
+        &mut self,
+        condition: impl Condition<M>,
+    ) -> &mut Self;
+    /// Track which `Statbar<T>`s with a `StatbarShowOnFocus<T>` are hovered by the cursor
+    fn add_statbar_focus<T: TypePath + 'static>(&mut self) -> &mut Self;
+    /// Count entities with the `M` marker component as selected,
+    /// for `Statbar<T>`s with a `StatbarShowOnFocus<T>`. Also calls `add_statbar_focus::<T>`.
+    fn add_statbar_selection_marker<T: TypePath + 'static, M: Component>(&mut self) -> &mut Self;
+}
+
+fn reset_statbar_type_condition<T>(mut visibility: ResMut<StatbarTypeVisibility<T>>)
+where
+    T: 'static + TypePath,
+{
+    visibility.condition_met = false;
+}
+
+fn set_statbar_type_condition<T>(mut visibility: ResMut<StatbarTypeVisibility<T>>)
+where
+    T: 'static + TypePath,
+{
+    visibility.condition_met = true;
+}
+
+/// Registration shared by all kinds of statbar, whatever the source of their values
+fn add_statbar_common<T: TypePath + 'static>(app: &mut App) -> &mut App {
+    if let Some(render_app) = app.get_sub_app_mut(bevy::render::RenderApp) {
+        render_app.add_systems(
+            ExtractSchedule,
+            extraction::extract_stat_bars::<T>.after(bevy::sprite::SpriteSystem::ExtractSprites),
+        );
+    }
+    mask::add_masks(app);
+
+    app.register_type::<Statbar<T>>()
+        .register_type::<StatbarBorder<T>>()
+        .register_type::<StatbarTexture<T>>()
+        .register_type::<StatbarSegments<T>>()
+        .register_type::<StatbarIcons<T>>()
+        .register_type::<StatbarOverflow<T>>()
+        .register_type::<StatbarStack<T>>()
+        .register_type::<StatbarPreview<T>>()
+        .register_type::<StatbarTrail<T>>()
+        .register_type::<StatbarTween<T>>()
+        .register_type::<StatbarFlash<T>>()
+        .register_type::<StatbarShake<T>>()
+        .register_type::<StatbarAlert<T>>()
+        .register_type::<StatbarAutoHide<T>>()
+        .register_type::<StatbarTypeVisibility<T>>()
+        .register_type::<StatbarShowOnFocus<T>>()
+        .register_type::<StatbarLengthFromMax<T>>()
+        .register_type::<StatbarColorLerp<T>>()
+        .register_type::<StatbarColorSwitch<T>>()
+        .register_type::<StatbarColorBands<T>>()
+        .register_type::<StatbarColorGradient<T>>()
+        .configure_sets(
+            PostUpdate,
+            (
+                StatbarSystem::UpdateValues,
+                StatbarSystem::TweenValues,
+                StatbarSystem::UpdateColors,
+                StatbarSystem::UpdateEffects,
+            )
+                .chain(),
+        )
+        .configure_sets(
+            PostUpdate,
+            (
+                StatbarColorSystem::Base,
+                StatbarColorSystem::Lerp,
+                StatbarColorSystem::Gradient,
+                StatbarColorSystem::Switch,
+                StatbarColorSystem::Bands,
+            )
+                .chain()
+                .in_set(StatbarSystem::UpdateColors),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                reset_stat_bar_colors::<T>.in_set(StatbarColorSystem::Base),
+                lerp_stat_bar_colors::<T>.in_set(StatbarColorSystem::Lerp),
+                gradient_stat_bar_colors::<T>.in_set(StatbarColorSystem::Gradient),
+                switch_stat_bar_colors::<T>.in_set(StatbarColorSystem::Switch),
+                band_stat_bar_colors::<T>.in_set(StatbarColorSystem::Bands),
+                tween_statbar_values::<T>.in_set(StatbarSystem::TweenValues),
+                update_statbar_lengths::<T>.in_set(StatbarSystem::TweenValues),
+                update_stat_bar_trails::<T>.in_set(StatbarSystem::UpdateEffects),
+                update_stat_bar_impacts::<T>.in_set(StatbarSystem::UpdateEffects),
+                update_stat_bar_auto_hide::<T>.in_set(StatbarSystem::UpdateEffects),
+                warn_statbar_focus_not_added::<T>
+                    .run_if(not(resource_exists::<StatbarFocusAdded<T>>))
+                    .in_set(StatbarSystem::UpdateEffects),
+            ),
+        );
+    #[cfg(feature = "labels")]
+    label::add_labels::<T>(app);
+    app
 }
 
 impl RegisterStatbarSubject for App {
     fn add_statbar_component_observer<T: StatbarObservable + Component + TypePath>(
         &mut self,
     ) -> &mut Self {
-        if let Some(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_systems(
-                ExtractSchedule,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
-            );
-        }
-
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
-            .configure_sets(
-                PostUpdate,
-                (StatbarSystem::UpdateValues, StatbarSystem::UpdateColors).chain(),
-            )
-            .add_systems(
-                PostUpdate,
-                (
-                    (
-                        update_statbar_values::<T>,
-                        update_statbar_values_from_other::<T>,
-                        update_statbar_values_from_parents::<T>,
-                    )
-                        .in_set(StatbarSystem::UpdateValues),
-                    (switch_stat_bar_colors::<T>, lerp_stat_bar_colors::<T>)
-                        .in_set(StatbarSystem::UpdateColors),
-                ),
+        add_statbar_common::<T>(self).add_systems(
+            PostUpdate,
+            (
+                update_statbar_values::<T>,
+                update_statbar_values_from_other::<T>,
+                update_statbar_values_from_parents::<T>,
             )
+                .in_set(StatbarSystem::UpdateValues),
+        )
     }
 
     fn add_statbar_resource_observer<
@@ -407,49 +2725,250 @@ impl RegisterStatbarSubject for App {
     >(
         &mut self,
     ) -> &mut Self {
-        if let Some(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_systems(
-                ExtractSchedule,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
-            );
-        }
+        add_statbar_common::<T>(self).add_systems(
+            PostUpdate,
+            update_statbar_from_resource::<T>.in_set(StatbarSystem::UpdateValues),
+        )
+    }
 
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
-            .configure_sets(
-                PostUpdate,
-                (StatbarSystem::UpdateValues, StatbarSystem::UpdateColors).chain(),
-            )
-            .add_systems(
-                PostUpdate,
-                (
-                    (update_statbar_from_resource::<T>,).in_set(StatbarSystem::UpdateValues),
-                    (switch_stat_bar_colors::<T>, lerp_stat_bar_colors::<T>)
-                        .in_set(StatbarSystem::UpdateColors),
-                ),
+    fn add_standalone_statbar<T: TypePath + 'static>(&mut self) -> &mut Self {
+        add_statbar_common::<T>(self)
+    }
+
+    fn add_statbar_stack_observer<T: TypePath + 'static, S: StatbarObservable + Component>(
+        &mut self,
+        segment: usize,
+    ) -> &mut Self {
+        self.add_systems(
+            PostUpdate,
+            update_statbar_stack_values::<T, S>(segment).in_set(StatbarSystem::TweenValues),
+        )
+    }
+
+    fn show_statbars_while<T: TypePath + 'static, M>(
+        &mut self,
+        condition: impl Condition<M>,
+    ) -> &mut Self {
+        self.insert_resource(StatbarTypeVisibility::<T>::new(
+            StatbarVisibilityMode::WhileCondition,
+        ))
+        .add_systems(
+            PostUpdate,
+            (
+                reset_statbar_type_condition::<T>,
+                set_statbar_type_condition::<T>.run_if(condition),
             )
+                .chain()
+                .in_set(StatbarSystem::UpdateEffects),
+        )
     }
 
-    fn add_standalone_statbar<T: TypePath + 'static>(&mut self) -> &mut Self {
-        if let Some(render_app) = self.get_sub_app_mut(bevy::render::RenderApp) {
-            render_app.add_systems(
-                ExtractSchedule,
-                extraction::extract_stat_bars::<T>
-                    .after(bevy::sprite::SpriteSystem::ExtractSprites),
+    fn add_statbar_focus<T: TypePath + 'static>(&mut self) -> &mut Self {
+        if self.world().contains_resource::<StatbarFocusAdded<T>>() {
+            return self;
+        }
+        if !self.world().contains_resource::<StatbarCursor>() {
+            self.init_resource::<StatbarCursor>().add_systems(
+                PostUpdate,
+                update_statbar_cursor.before(StatbarSystem::UpdateEffects),
             );
         }
-
-        self.register_type::<Statbar<T>>()
-            .register_type::<StatbarBorder<T>>()
-            .register_type::<StatbarColorLerp<T>>()
-            .register_type::<StatbarColorSwitch<T>>()
+        self.insert_resource(StatbarFocusAdded::<T>(PhantomData))
             .add_systems(
                 PostUpdate,
-                ((switch_stat_bar_colors::<T>, lerp_stat_bar_colors::<T>)
-                    .in_set(StatbarSystem::UpdateColors),),
+                update_statbar_hover::<T>.in_set(StatbarSystem::UpdateEffects),
             )
     }
+
+    fn add_statbar_selection_marker<T: TypePath + 'static, M: Component>(&mut self) -> &mut Self {
+        self.add_statbar_focus::<T>().add_systems(
+            PostUpdate,
+            update_statbar_selection::<T, M>
+                .after(update_statbar_hover::<T>)
+                .in_set(StatbarSystem::UpdateEffects),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_from_max_finishes_animating_within_duration() {
+        let mut length = StatbarLengthFromMax::<()>::new(10.).animated(1.0, StatbarEasing::Linear);
+        assert_eq!(length.update(10., 0.1), 100.);
+        let mut current = 0.;
+        for step in 1..=10 {
+            current = length.update(20., 0.1);
+            if step == 5 {
+                assert!((current - 150.).abs() < 1e-3);
+            }
+        }
+        assert!((current - 200.).abs() < 1e-3);
+        assert_eq!(length.update(20., 0.1), 200.);
+    }
+
+    #[test]
+    fn bipolar_values_are_remapped_for_colors() {
+        let mut bar = Statbar::<()> {
+            value: -0.5,
+            ..Default::default()
+        };
+        assert_eq!(bar.unipolar_value(), -0.5);
+        bar.bipolar = Some(StatbarBipolar::default());
+        assert_eq!(bar.unipolar_value(), 0.25);
+    }
+
+    #[test]
+    fn color_bands_only_change_band_past_the_hysteresis() {
+        let low = Color::from(bevy::color::palettes::css::RED);
+        let high = Color::from(bevy::color::palettes::css::GREEN);
+        let mut bands = StatbarColorBands::<()>::new(low, [(0.7, Color::WHITE), (0.3, high)])
+            .with_hysteresis(0.05);
+        assert_eq!(bands.update(0.1), low);
+        assert_eq!(bands.update(0.32), low);
+        assert_eq!(bands.update(0.36), high);
+        assert_eq!(bands.update(0.28), high);
+        assert_eq!(bands.update(0.24), low);
+        assert_eq!(bands.update(0.9), Color::WHITE);
+        assert_eq!(bands.update(0.66), Color::WHITE);
+        assert_eq!(bands.update(0.5), high);
+    }
+
+    #[test]
+    fn color_bands_without_hysteresis_follow_the_value() {
+        let mut bands = StatbarColorBands::<()>::new(Color::BLACK, [(0.5, Color::WHITE)]);
+        assert_eq!(bands.update(0.5), Color::BLACK);
+        assert_eq!(bands.update(0.51), Color::WHITE);
+        assert_eq!(bands.update(0.49), Color::BLACK);
+    }
+
+    #[test]
+    fn separators_split_the_bar_without_one_on_the_end() {
+        let separators = |segmentation: StatbarSegmentation, observed_max: f32| {
+            segmentation.separators(observed_max).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            separators(StatbarSegmentation::Count(4), 0.),
+            [0.25, 0.5, 0.75]
+        );
+        assert!(separators(StatbarSegmentation::Count(0), 0.).is_empty());
+        assert_eq!(
+            separators(
+                StatbarSegmentation::Amount {
+                    amount: 100.,
+                    max: 250.
+                },
+                0.
+            ),
+            [0.4, 0.8]
+        );
+        assert_eq!(
+            separators(StatbarSegmentation::ObservedAmount(0.1), 0.3).len(),
+            2
+        );
+        assert_eq!(
+            separators(StatbarSegmentation::ObservedAmount(1.), 1000.).len(),
+            999
+        );
+        assert!(separators(StatbarSegmentation::ObservedAmount(1.), 1e6).is_empty());
+        assert!(separators(StatbarSegmentation::ObservedAmount(0.), 100.).is_empty());
+        assert!(separators(StatbarSegmentation::ObservedAmount(1.), f32::NAN).is_empty());
+    }
+
+    #[test]
+    fn separators_are_skipped_when_closer_than_their_width() {
+        let segments = StatbarSegments::<()>::new(StatbarSegmentation::Count(10))
+            .with_separators(Color::BLACK, 2.);
+        assert_eq!(segments.separators(0., 100.).count(), 9);
+        assert_eq!(segments.separators(0., 19.).count(), 0);
+    }
+
+    #[test]
+    fn icons_follow_the_observed_max_up_to_a_limit() {
+        let icons = StatbarIcons::<()>::new(Handle::default(), Handle::default(), 3)
+            .per_observed_amount(20.);
+        assert_eq!(icons.fill(0.5, 100.), (5, 2.5));
+        assert_eq!(icons.fill(1., 90.), (5, 4.5));
+        assert_eq!(icons.fill(0.5, 1e9), (100, 50.));
+        assert_eq!(
+            StatbarIcons::<()>::new(Handle::default(), Handle::default(), 3).fill(0.5, 1e9),
+            (3, 1.5)
+        );
+    }
+
+    #[derive(Component, TypePath)]
+    struct Health(f32);
+
+    impl StatbarObservable for Health {
+        fn get_statbar_value(&self) -> f32 {
+            self.0 / 100.
+        }
+
+        fn get_statbar_current(&self) -> f32 {
+            self.0
+        }
+
+        fn get_statbar_max(&self) -> f32 {
+            100.
+        }
+    }
+
+    #[derive(Component, TypePath)]
+    struct Shield(f32);
+
+    impl StatbarObservable for Shield {
+        fn get_statbar_current(&self) -> f32 {
+            self.0
+        }
+
+        fn get_statbar_max(&self) -> f32 {
+            50.
+        }
+    }
+
+    #[test]
+    fn value_is_derived_from_current_min_and_max() {
+        struct Heat(f32);
+
+        impl StatbarObservable for Heat {
+            fn get_statbar_current(&self) -> f32 {
+                self.0
+            }
+
+            fn get_statbar_max(&self) -> f32 {
+                120.
+            }
+
+            fn get_statbar_min(&self) -> Option<f32> {
+                Some(20.)
+            }
+        }
+
+        assert_eq!(Heat(45.).get_statbar_value(), 0.25);
+        assert_eq!(Shield(25.).get_statbar_value(), 0.5);
+        assert_eq!(Health(25.).get_statbar_value(), 0.25);
+    }
+
+    #[test]
+    fn stack_segments_share_the_scale_of_the_bar() {
+        let mut app = App::new();
+        app.add_statbar_component_observer::<Health>()
+            .add_statbar_stack_observer::<Health, Shield>(0);
+        let owner = app.world_mut().spawn((Health(100.), Shield(50.))).id();
+        let bar = app
+            .world_mut()
+            .spawn((
+                Statbar::<Health>::default(),
+                StatbarStack::<Health>::new([Color::WHITE]),
+                StatbarObserveEntity(owner),
+            ))
+            .id();
+        app.update();
+        let world = app.world();
+        assert_eq!(world.get::<Statbar<Health>>(bar).unwrap().value, 1.);
+        let stack = world.get::<StatbarStack<Health>>(bar).unwrap();
+        assert_eq!(stack.segments[0].value, 0.5);
+    }
 }
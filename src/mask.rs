@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+
+/// White rounded rectangle used to draw rounded statbars as nine-slices.
+pub(crate) const ROUNDED_MASK_HANDLE: Handle<Image> =
+    Handle::weak_from_u128(0x5b1c_83e0_6f2a_4d57_9e41_0b7d_c2a8_f136);
+
+/// Wedge used to draw the segments of radial statbars.
+///
+/// With u running from 0.0 to 1.0 across the image and v from -1.0 to 1.0 down it, covers
+/// the pixels with `|v| <= u`. Cropping the image to `k <= u` gives an isosceles trapezoid
+/// whose slanted edges meet those of the neighbouring segments exactly.
+pub(crate) const WEDGE_MASK_HANDLE: Handle<Image> =
+    Handle::weak_from_u128(0x2d94_6a1e_b750_43c8_8f0e_57a3_19c4_e62b);
+
+/// Width and height of the wedge mask in pixels.
+pub(crate) const WEDGE_MASK_SIZE: u32 = 256;
+
+/// Radius of the corners of the mask in pixels.
+pub(crate) const MASK_RADIUS: f32 = 32.;
+
+/// The mask is two pixels wider than its corners, so the stretched center slices
+/// never sample the curves.
+const MASK_SIZE: u32 = 2 * MASK_RADIUS as u32 + 2;
+
+pub(crate) fn mask_size() -> Vec2 {
+    Vec2::splat(MASK_SIZE as f32)
+}
+
+/// Inserted once the masks are added, or scheduled to be added at startup.
+#[derive(Resource)]
+struct StatbarMasksAdded;
+
+/// Adds the masks to the image assets.
+/// If there are no image assets yet, the masks are added at startup instead.
+pub(crate) fn add_masks(app: &mut App) {
+    if app.world().contains_resource::<StatbarMasksAdded>() {
+        return;
+    }
+    app.insert_resource(StatbarMasksAdded);
+    match app.world_mut().get_resource_mut::<Assets<Image>>() {
+        Some(mut images) => insert_masks(&mut images),
+        None => {
+            app.add_systems(Startup, insert_masks_at_startup);
+        }
+    }
+}
+
+fn insert_masks_at_startup(images: Option<ResMut<Assets<Image>>>) {
+    match images {
+        Some(mut images) => insert_masks(&mut images),
+        None => {
+            warn!("bevy_stat_bars: no `Assets<Image>` resource, rounded and radial statbars won't be drawn");
+        }
+    }
+}
+
+fn insert_masks(images: &mut Assets<Image>) {
+    if !images.contains(&ROUNDED_MASK_HANDLE) {
+        images.insert(&ROUNDED_MASK_HANDLE, rounded_mask());
+    }
+    if !images.contains(&WEDGE_MASK_HANDLE) {
+        images.insert(&WEDGE_MASK_HANDLE, wedge_mask());
+    }
+}
+
+fn rounded_mask() -> Image {
+    let size = MASK_SIZE as f32;
+    let mut data = Vec::with_capacity((MASK_SIZE * MASK_SIZE * 4) as usize);
+    for y in 0..MASK_SIZE {
+        for x in 0..MASK_SIZE {
+            let point = Vec2::new(x as f32, y as f32) + 0.5;
+            let corner = point.clamp(Vec2::splat(MASK_RADIUS), Vec2::splat(size - MASK_RADIUS));
+            let coverage = (MASK_RADIUS - point.distance(corner) + 0.5).clamp(0., 1.);
+            data.extend_from_slice(&[255, 255, 255, (coverage * 255.).round() as u8]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: MASK_SIZE,
+            height: MASK_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::linear();
+    image
+}
+
+fn wedge_mask() -> Image {
+    let size = WEDGE_MASK_SIZE as f32;
+    let mut data = Vec::with_capacity((WEDGE_MASK_SIZE * WEDGE_MASK_SIZE * 4) as usize);
+    for y in 0..WEDGE_MASK_SIZE {
+        for x in 0..WEDGE_MASK_SIZE {
+            let u = (x as f32 + 0.5) / size;
+            let v = 2. * (y as f32 + 0.5) / size - 1.;
+            let alpha = if v.abs() <= u { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: WEDGE_MASK_SIZE,
+            height: WEDGE_MASK_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // blended edges would leave faint seams where neighbouring segments meet
+    image.sampler = ImageSampler::nearest();
+    image
+}